@@ -0,0 +1,54 @@
+//! Cuts a real appinfo.vdf down to a few apps so it can be kept as a test fixture.
+//! The apps are copied byte for byte, v29's string table is kept whole since the apps' keys point into it.
+//!
+//! `cargo run -p sdscanner --example trim_appinfo -- ~/.steam/steam/appcache/appinfo.vdf appinfo_v29.vdf 1245620 413150`
+
+use std::{env, fs, process};
+
+const MAGIC_V29: u32 = 0x0756_4429;
+
+fn u32_at(input: &[u8], position: usize) -> u32 {
+    u32::from_le_bytes(input[position..position + 4].try_into().unwrap())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() < 3 {
+        eprintln!("Usage: trim_appinfo <appinfo.vdf> <output> <appid>...");
+        process::exit(2);
+    }
+    let keep: Vec<u32> = args[2..]
+        .iter()
+        .map(|appid| appid.parse().expect("App IDs are numbers"))
+        .collect();
+    let input = fs::read(&args[0]).expect("Couldn't read the appinfo.vdf");
+
+    let magic = u32_at(&input, 0);
+    let header_size = if magic == MAGIC_V29 { 16 } else { 8 };
+    let mut output = input[..header_size].to_vec();
+
+    // Each app is its ID, the size of the rest of its entry, then the rest of its entry
+    let mut position = header_size;
+    loop {
+        let appid = u32_at(&input, position);
+        if appid == 0 {
+            break;
+        }
+        let end = position + 8 + u32_at(&input, position + 4) as usize;
+        if keep.contains(&appid) {
+            output.extend(&input[position..end]);
+        }
+        position = end;
+    }
+    output.extend(0u32.to_le_bytes());
+
+    if magic == MAGIC_V29 {
+        let table = i64::from_le_bytes(input[8..16].try_into().unwrap()) as usize;
+        let offset = output.len() as i64;
+        output[8..16].copy_from_slice(&offset.to_le_bytes());
+        output.extend(&input[table..]);
+    }
+
+    fs::write(&args[1], &output).expect("Couldn't write the trimmed file");
+    println!("Wrote {} bytes to {}", output.len(), args[1]);
+}
@@ -0,0 +1,112 @@
+use crate::error::Error;
+use crate::steam;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// The size covers are shrunk to for the grid, the same 2:3 shape as Steam's library capsules
+pub const THUMBNAIL_WIDTH: u32 = 160;
+pub const THUMBNAIL_HEIGHT: u32 = 240;
+
+/// A game's cover shrunk down for the grid, as RGBA pixels
+#[derive(Debug, Clone)]
+pub struct Thumbnail {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// Steam's cached art for the app, the tall library capsule if it has one, otherwise the wide header
+/// or the icon. Newer Steam clients keep each app's art in its own folder
+fn source_path(appid: u32) -> Option<PathBuf> {
+    let cache = steam::root()?.join("appcache/librarycache");
+
+    [
+        cache.join(format!("{appid}_library_600x900.jpg")),
+        cache.join(format!("{appid}/library_600x900.jpg")),
+        cache.join(format!("{appid}_header.jpg")),
+        cache.join(format!("{appid}/header.jpg")),
+        cache.join(format!("{appid}_icon.jpg")),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())
+}
+
+/// Where the shrunk covers are kept so the grid doesn't decode the full size art every time
+fn cache_path(appid: u32) -> Option<PathBuf> {
+    Some(
+        dirs::cache_dir()?
+            .join("sdscanner-thumbnails")
+            .join(format!("{appid}.png")),
+    )
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+/// The app's cover for the grid, from the thumbnail cache if Steam's art hasn't changed since it was made.
+/// None if Steam has no art cached for it, e.g. it was never shown in this Deck's library.
+/// Art that can't be read or cached is added to `warnings`
+pub fn load_thumbnail(appid: u32, warnings: &mut Vec<Error>) -> Option<Thumbnail> {
+    let source = source_path(appid)?;
+    let cache = cache_path(appid);
+
+    if let Some(cache) = &cache {
+        let fresh = match (modified(cache), modified(&source)) {
+            (Some(cached), Some(changed)) => cached >= changed,
+            _ => false,
+        };
+        if fresh {
+            if let Ok(image) = image::open(cache) {
+                return Some(to_thumbnail(image));
+            }
+        }
+    }
+
+    let image = match image::open(&source) {
+        Ok(image) => image.thumbnail(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT),
+        Err(e) => {
+            warnings.push(Error::Invalid(format!(
+                "Couldn't read the artwork at {}: {e}",
+                source.display()
+            )));
+            return None;
+        }
+    };
+
+    if let Some(cache) = &cache {
+        // Not being able to cache it only makes the next load slower
+        let saved = cache
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .map_err(|e| e.to_string())
+            .and_then(|()| image.save(cache).map_err(|e| e.to_string()));
+        if let Err(e) = saved {
+            warnings.push(Error::Invalid(format!(
+                "Couldn't cache the thumbnail at {}: {e}",
+                cache.display()
+            )));
+        }
+    }
+
+    Some(to_thumbnail(image))
+}
+
+fn to_thumbnail(image: image::DynamicImage) -> Thumbnail {
+    let image = image.into_rgba8();
+    Thumbnail {
+        width: image.width(),
+        height: image.height(),
+        pixels: image.into_raw(),
+    }
+}
+
+/// The letters shown on a game's placeholder tile, the first letter of its first two words
+pub fn initials(name: &str) -> String {
+    name.split(|c: char| c.is_whitespace() || c == '-' || c == ':')
+        .filter_map(|word| word.chars().find(|c| c.is_alphanumeric()))
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect()
+}
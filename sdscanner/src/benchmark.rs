@@ -0,0 +1,254 @@
+use crate::error::Error;
+use crate::scanning::{self, Card};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How much of the biggest game files the sequential read test reads
+const SEQUENTIAL_READ_BYTES: u64 = 256 * 1024 * 1024;
+/// How many 4 KiB reads the random read test does, it also stops after `RANDOM_READ_TIME`
+const RANDOM_READS: u32 = 2000;
+const RANDOM_READ_TIME: Duration = Duration::from_secs(5);
+const RANDOM_READ_SIZE: usize = 4096;
+/// Files smaller than this aren't used for the random reads
+const RANDOM_READ_MIN_FILE: u64 = 1024 * 1024;
+/// The size of the temporary file the write test writes
+const WRITE_BYTES: u64 = 128 * 1024 * 1024;
+const BUFFER_SIZE: usize = 1024 * 1024;
+/// How many game files are looked at to pick the test files from
+const MAX_FILES: usize = 20_000;
+/// How many results are kept on each card
+pub const KEPT_RESULTS: usize = 10;
+
+/// The temporary file the write test writes to the card's root, removed once it's done
+const WRITE_TEST_FILE: &str = ".sdscanner-benchmark.tmp";
+
+/// The speeds measured by one benchmark run
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BenchmarkResult {
+    /// When the benchmark ran, as a unix timestamp
+    pub timestamp: u64,
+    /// Reading the biggest game files start to end, in MB/s
+    pub sequential_read: f64,
+    /// 4 KiB reads at random spots in the game files, in MB/s
+    pub random_read: f64,
+    /// The random reads per second
+    pub random_read_iops: f64,
+    /// Writing a temporary file to the card, in MB/s. None if the write test wasn't run
+    #[serde(default)]
+    pub sequential_write: Option<f64>,
+}
+
+impl BenchmarkResult {
+    /// A one line description of the speeds, e.g. `Read 85.2 MB/s, Random 4K 6.1 MB/s (1490 IOPS)`
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "Read {:.1} MB/s, Random 4K {:.1} MB/s ({:.0} IOPS)",
+            self.sequential_read, self.random_read, self.random_read_iops
+        );
+        if let Some(write) = self.sequential_write {
+            summary.push_str(&format!(", Write {write:.1} MB/s"));
+        }
+        summary
+    }
+}
+
+/// Runs the read tests on the card's game files, and the write test if `write_test` is set.
+/// The card has to be inserted and mounted at its `mount_path`
+pub fn run(card: &Card, write_test: bool) -> Result<BenchmarkResult, Error> {
+    let mut roots = vec![card.mount_path.join("steamapps/common")];
+    roots.extend(
+        [&card.lutris, &card.heroic]
+            .into_iter()
+            .flatten()
+            .map(|library| library.path.clone()),
+    );
+
+    let mut files = vec![];
+    for root in roots.iter().filter(|root| root.is_dir()) {
+        collect_files(root, &mut files);
+    }
+    if files.is_empty() {
+        return Err(Error::Invalid(format!(
+            "No game files found to read on {}",
+            card.name
+        )));
+    }
+    // Biggest first, they give the steadiest sequential reads
+    files.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+
+    let sequential_read = sequential_read(&files)?;
+    let (random_read, random_read_iops) = random_read(&files)?;
+    let sequential_write = if write_test {
+        Some(sequential_write(&card.mount_path)?)
+    } else {
+        None
+    };
+
+    Ok(BenchmarkResult {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs()),
+        sequential_read,
+        random_read,
+        random_read_iops,
+        sequential_write,
+    })
+}
+
+/// Adds every file under the folder with its size to `files`, up to `MAX_FILES`
+fn collect_files(dir: &Path, files: &mut Vec<(PathBuf, u64)>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        if files.len() >= MAX_FILES {
+            return;
+        }
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+
+        if metadata.is_dir() {
+            collect_files(&entry.path(), files);
+        } else if metadata.is_file() && metadata.len() > 0 {
+            files.push((entry.path(), metadata.len()));
+        }
+    }
+}
+
+/// Opens a file and asks the kernel to forget any of it that's cached, so the reads come from the card
+fn open_uncached(path: &Path) -> Result<File, Error> {
+    let file = File::open(path).map_err(Error::io("open", path))?;
+    // SAFETY: the descriptor belongs to the open file, fadvise only changes the kernel's cache
+    unsafe {
+        libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED);
+    }
+    Ok(file)
+}
+
+fn megabytes_per_second(bytes: u64, elapsed: Duration) -> f64 {
+    bytes as f64 / 1_000_000.0 / elapsed.as_secs_f64().max(0.001)
+}
+
+fn sequential_read(files: &[(PathBuf, u64)]) -> Result<f64, Error> {
+    let mut buffer = vec![0; BUFFER_SIZE];
+    let mut total: u64 = 0;
+    let start = Instant::now();
+
+    for (path, _) in files {
+        let mut file = open_uncached(path)?;
+        loop {
+            let read = file.read(&mut buffer).map_err(Error::io("read", path))?;
+            if read == 0 {
+                break;
+            }
+            total += read as u64;
+            if total >= SEQUENTIAL_READ_BYTES {
+                return Ok(megabytes_per_second(total, start.elapsed()));
+            }
+        }
+    }
+
+    Ok(megabytes_per_second(total, start.elapsed()))
+}
+
+/// Returns (MB/s, reads per second)
+fn random_read(files: &[(PathBuf, u64)]) -> Result<(f64, f64), Error> {
+    let mut big_files = vec![];
+    for (path, size) in files
+        .iter()
+        .filter(|(_, size)| *size >= RANDOM_READ_MIN_FILE)
+        .take(64)
+    {
+        big_files.push((path, open_uncached(path)?, *size));
+    }
+    if big_files.is_empty() {
+        return Err(Error::Invalid(String::from(
+            "No game files big enough for the random read test",
+        )));
+    }
+
+    let mut random = Xorshift::seeded();
+    let mut buffer = vec![0; RANDOM_READ_SIZE];
+    let mut reads: u32 = 0;
+    let start = Instant::now();
+
+    while reads < RANDOM_READS && start.elapsed() < RANDOM_READ_TIME {
+        let index = random.next() as usize % big_files.len();
+        let (path, file, size) = &mut big_files[index];
+        let blocks = *size / RANDOM_READ_SIZE as u64;
+        let offset = random.next() % blocks * RANDOM_READ_SIZE as u64;
+
+        file.seek(SeekFrom::Start(offset))
+            .and_then(|_| file.read_exact(&mut buffer))
+            .map_err(Error::io("read", path))?;
+        reads += 1;
+    }
+
+    let elapsed = start.elapsed();
+    Ok((
+        megabytes_per_second(reads as u64 * RANDOM_READ_SIZE as u64, elapsed),
+        reads as f64 / elapsed.as_secs_f64().max(0.001),
+    ))
+}
+
+fn sequential_write(card_path: &Path) -> Result<f64, Error> {
+    let free = scanning::free_space(card_path)?;
+    if free < WRITE_BYTES * 2 {
+        return Err(Error::Invalid(String::from(
+            "Not enough free space on the card for the write test",
+        )));
+    }
+
+    let path = card_path.join(WRITE_TEST_FILE);
+    let result = write_test_file(&path);
+    // A failed write is the more useful error, the leftover file only matters if the test worked
+    let removed = fs::remove_file(&path).map_err(Error::io("remove the write test file", &path));
+    let speed = result?;
+    removed?;
+    Ok(speed)
+}
+
+fn write_test_file(path: &Path) -> Result<f64, Error> {
+    let error = |e: std::io::Error| Error::io("write", path)(e);
+
+    let buffer: Vec<u8> = (0..BUFFER_SIZE).map(|i| (i % 251) as u8).collect();
+    let start = Instant::now();
+
+    let mut file = File::create(path).map_err(error)?;
+    let mut written: u64 = 0;
+    while written < WRITE_BYTES {
+        file.write_all(&buffer).map_err(error)?;
+        written += buffer.len() as u64;
+    }
+    // Only count it once the data is actually on the card
+    file.sync_all().map_err(error)?;
+
+    Ok(megabytes_per_second(written, start.elapsed()))
+}
+
+/// A small random number generator for picking the random read spots, no need for anything stronger
+struct Xorshift(u64);
+
+impl Xorshift {
+    fn seeded() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64);
+        Xorshift(seed | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
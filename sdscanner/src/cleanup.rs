@@ -0,0 +1,147 @@
+use crate::error::Error;
+use crate::scanning::{self, Card};
+use crate::steam;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Non-Steam shortcuts get app IDs from here up, their Proton prefixes have no appmanifest but are still in use
+const SHORTCUT_APPIDS: u64 = 1 << 31;
+
+/// The folders in steamapps that are named after app IDs and can outlive the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeftoverKind {
+    ShaderCache,
+    CompatData,
+    /// An unfinished download or update
+    Downloading,
+    Temp,
+}
+
+impl LeftoverKind {
+    pub const ALL: [LeftoverKind; 4] = [
+        LeftoverKind::ShaderCache,
+        LeftoverKind::CompatData,
+        LeftoverKind::Downloading,
+        LeftoverKind::Temp,
+    ];
+
+    /// A Proton prefix keeps the saves of a Windows game, which can be installed on another card or
+    /// the internal drive, or be played again later. They're never removed along with the other leftovers
+    pub fn holds_saves(&self) -> bool {
+        *self == LeftoverKind::CompatData
+    }
+
+    /// The folder in steamapps it's in
+    pub fn folder(&self) -> &'static str {
+        match self {
+            LeftoverKind::ShaderCache => "shadercache",
+            LeftoverKind::CompatData => "compatdata",
+            LeftoverKind::Downloading => "downloading",
+            LeftoverKind::Temp => "temp",
+        }
+    }
+}
+
+/// A folder left behind by a game that's no longer installed on the card
+#[derive(Debug, Clone)]
+pub struct Leftover {
+    pub kind: LeftoverKind,
+    pub path: PathBuf,
+    /// The app ID the folder or download state file is named after
+    pub appid: u32,
+    pub bytes: u64,
+}
+
+impl Leftover {
+    /// e.g. `compatdata/1245620`
+    pub fn name(&self) -> String {
+        let file_name = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        format!("{}/{file_name}", self.kind.folder())
+    }
+}
+
+/// The name of the game the leftover belonged to, if it's installed on any saved card
+pub fn game_name(list: &[Card], appid: u32) -> Option<&str> {
+    list.iter()
+        .flat_map(|card| &card.games)
+        .find(|game| game.appid == Some(appid))
+        .map(|game| game.name.as_str())
+}
+
+/// Finds the shadercache, compatdata, downloading and temp entries in the card's steamapps folder
+/// that don't belong to a game with an appmanifest, biggest first
+pub fn find_leftovers(card_path: &Path) -> Vec<Leftover> {
+    let steamapps = card_path.join("steamapps");
+    let installed: HashSet<u32> = scanning::read_app_manifests(&steamapps)
+        .values()
+        .map(|manifest| manifest.appid)
+        .collect();
+
+    let mut leftovers = vec![];
+    for kind in LeftoverKind::ALL {
+        let entries = match fs::read_dir(steamapps.join(kind.folder())) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            // Download state files are named like state_1245620_1245621.patch
+            let appid: Option<u64> = file_name
+                .trim_start_matches("state_")
+                .split(|c: char| !c.is_ascii_digit())
+                .next()
+                .and_then(|digits| digits.parse().ok());
+
+            // Anything not named after an app ID is left alone
+            let appid = match appid {
+                Some(appid) if appid < SHORTCUT_APPIDS => appid as u32,
+                _ => continue,
+            };
+            if installed.contains(&appid) {
+                continue;
+            }
+
+            let bytes = match entry.metadata() {
+                Ok(metadata) if metadata.is_dir() => scanning::dir_size(&path),
+                Ok(metadata) => metadata.len(),
+                Err(_) => 0,
+            };
+            leftovers.push(Leftover {
+                kind,
+                path,
+                appid,
+                bytes,
+            });
+        }
+    }
+
+    leftovers.sort_by_key(|leftover| std::cmp::Reverse(leftover.bytes));
+    leftovers
+}
+
+/// Deletes the leftovers, refuses while Steam is running since it could be using the download folders
+pub fn remove(leftovers: &[Leftover]) -> Result<(), Error> {
+    if steam::running() {
+        return Err(Error::SteamRunning {
+            doing: "removing leftover folders",
+        });
+    }
+
+    for leftover in leftovers {
+        let result = if leftover.path.is_dir() {
+            fs::remove_dir_all(&leftover.path)
+        } else {
+            fs::remove_file(&leftover.path)
+        };
+        result.map_err(Error::io("remove", &leftover.path))?;
+    }
+
+    Ok(())
+}
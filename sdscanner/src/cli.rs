@@ -0,0 +1,237 @@
+//! The command line commands of the sdscanner binary and the app, behind the `cli` feature.
+//! This is the only part of the library that prints
+
+use crate::cleanup::Leftover;
+use crate::error::Error;
+use crate::preferences::Preferences;
+use crate::scanning::Card;
+use crate::transfer::{self, Transfer};
+use crate::{benchmark, cleanup, identity, scanning, steam};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "Usage: sdscanner <command>
+
+steamdeck_sd_card_scanner takes the same commands, and opens the app's window without one.
+
+Commands:
+  benchmark [--write]   test the speed of the inserted cards, --write also runs the write test
+  benchmark --list      show the saved speed test results of every card
+  copy <game> <to>      copy a Steam game folder to an inserted card, by its name, or to internal
+  move <game> <to>      the same as copy, then remove the original once the copy is verified
+  leftovers             list the folders left behind by removed games on the inserted cards
+  help                  show this message";
+
+/// Runs the command line command in `args`, returns false if there's no command and the window should open instead
+pub fn run(args: &[String], preferences: &Preferences) -> bool {
+    let command = match args.first() {
+        Some(command) => command.as_str(),
+        None => return false,
+    };
+    let flags = &args[1..];
+
+    match command {
+        "benchmark" if flags.iter().any(|flag| flag == "--list") => list_benchmarks(),
+        "benchmark" => run_benchmarks(flags.iter().any(|flag| flag == "--write"), preferences),
+        "copy" | "move" => match flags {
+            [game, to] => run_transfer(game, to, command == "move", preferences),
+            _ => {
+                eprintln!("{USAGE}");
+                std::process::exit(2);
+            }
+        },
+        "leftovers" => list_leftovers(preferences),
+        "help" | "--help" | "-h" => println!("{USAGE}"),
+        _ => {
+            eprintln!("Unknown command {command}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    }
+
+    true
+}
+
+/// Tests every inserted card and saves the results to the card list
+fn run_benchmarks(write_test: bool, preferences: &Preferences) {
+    let mut list = scanned_cards(preferences);
+    let inserted: HashSet<String> = list
+        .iter()
+        .filter(|card| scanning::inserted_card_path(&list, &card.uuid).is_some())
+        .map(|card| card.uuid.clone())
+        .collect();
+
+    if inserted.is_empty() {
+        eprintln!("No inserted cards found");
+        std::process::exit(1);
+    }
+
+    for card in list.iter_mut().filter(|card| inserted.contains(&card.uuid)) {
+        println!("Testing {}...", card.name);
+
+        match benchmark::run(card, write_test) {
+            Ok(result) => {
+                println!("  {}", result.summary());
+                card.add_benchmark(result);
+            }
+            Err(problem) => eprintln!("  {problem}"),
+        }
+    }
+
+    or_exit(scanning::save_cards(&list));
+}
+
+fn list_benchmarks() {
+    let list = or_exit(scanning::load_cards());
+
+    for card in &list {
+        println!("{} ({})", card.name, card.uuid);
+        if card.benchmarks.is_empty() {
+            println!("  Untested");
+        }
+        for result in card.benchmarks.iter().rev() {
+            println!(
+                "  {}  {}",
+                scanning::format_date(result.timestamp),
+                result.summary()
+            );
+        }
+    }
+}
+
+/// Copies or moves a Steam game between the inserted cards and the internal drive, printing the progress
+fn run_transfer(game: &str, to: &str, delete_source: bool, preferences: &Preferences) {
+    let mut list = scanned_cards(preferences);
+
+    // (card UUID, library root) of every library, None for the internal drive
+    let mut libraries: Vec<(Option<String>, PathBuf)> = or_exit(scanning::discover_cards())
+        .into_iter()
+        .filter_map(|scanned| {
+            let card = &list[identity::find_card(&list, &scanned)?];
+            Some((Some(card.uuid.clone()), scanned.card_path))
+        })
+        .collect();
+    libraries.extend(steam::root().map(|path| (None, path)));
+
+    let library_name = |uuid: &Option<String>| match uuid {
+        Some(uuid) => list
+            .iter()
+            .find(|card| &card.uuid == uuid)
+            .map_or_else(|| uuid.clone(), |card| card.name.clone()),
+        None => String::from("internal"),
+    };
+
+    let from = libraries
+        .iter()
+        .find(|(_, path)| path.join("steamapps/common").join(game).is_dir());
+    let destination = libraries.iter().find(|(uuid, _)| library_name(uuid) == to);
+
+    let ((from_uuid, from_path), (to_uuid, to_path)) = match (from, destination) {
+        (None, _) => {
+            eprintln!("{game} wasn't found on the inserted cards or the internal drive");
+            std::process::exit(1);
+        }
+        (_, None) => {
+            eprintln!("{to} isn't an inserted card's name or internal");
+            std::process::exit(1);
+        }
+        (Some(from), Some(destination)) => (from.clone(), destination.clone()),
+    };
+
+    let appid = match scanning::read_app_manifests(&from_path.join("steamapps")).get(game) {
+        Some(manifest) => manifest.appid,
+        None => {
+            eprintln!("Steam's appmanifest for {game} wasn't found");
+            std::process::exit(1);
+        }
+    };
+
+    let transfer = Transfer {
+        game: game.to_string(),
+        appid,
+        from: from_path,
+        to: to_path,
+        delete_source,
+    };
+    let result = transfer::run(&transfer, |progress| {
+        print!("\r{}    ", progress.summary());
+        let _ = std::io::stdout().flush();
+    });
+    println!();
+
+    if let Err(problem) = result {
+        eprintln!("{problem}");
+        std::process::exit(1);
+    }
+
+    transfer::record(
+        &mut list,
+        &transfer,
+        from_uuid.as_deref(),
+        to_uuid.as_deref(),
+    );
+    or_exit(scanning::save_cards(&list));
+    println!(
+        "{} {game} to {to}",
+        if delete_source { "Moved" } else { "Copied" }
+    );
+}
+
+/// Prints the leftover shadercache, compatdata and download folders on each inserted card
+fn list_leftovers(preferences: &Preferences) {
+    let list = scanned_cards(preferences);
+    let inserted = or_exit(scanning::discover_cards());
+    if inserted.is_empty() {
+        eprintln!("No inserted cards found");
+        std::process::exit(1);
+    }
+
+    for scanned in inserted {
+        let name = identity::find_card(&list, &scanned)
+            .map_or_else(|| scanned.uuid.clone(), |index| list[index].name.clone());
+        let leftovers = cleanup::find_leftovers(&scanned.card_path);
+        let total: u64 = leftovers.iter().map(|leftover| leftover.bytes).sum();
+        println!("{name}: {}", scanning::format_size(total));
+
+        // The Proton prefixes go last with a warning, they hold the saves of games that might be installed elsewhere
+        let (prefixes, others): (Vec<&Leftover>, Vec<&Leftover>) = leftovers
+            .iter()
+            .partition(|leftover| leftover.kind.holds_saves());
+        print_leftovers(&list, &others);
+        if !prefixes.is_empty() {
+            println!("  Proton prefixes, removing these deletes the game saves in them:");
+            print_leftovers(&list, &prefixes);
+        }
+    }
+}
+
+fn print_leftovers(list: &[Card], leftovers: &[&Leftover]) {
+    for leftover in leftovers {
+        let game = cleanup::game_name(list, leftover.appid)
+            .map(|game| format!("  ({game})"))
+            .unwrap_or_default();
+        println!(
+            "  {:>10}  {}{game}",
+            scanning::format_size(leftover.bytes),
+            leftover.name()
+        );
+    }
+}
+
+/// The saved cards updated with the inserted ones, anything the scan skipped is printed
+fn scanned_cards(preferences: &Preferences) -> Vec<Card> {
+    let mut list = or_exit(scanning::load_cards());
+    let problems = scanning::update_list(&mut list, preferences).unwrap_or_else(|e| vec![e]);
+    for problem in problems {
+        eprintln!("{problem}");
+    }
+    or_exit(scanning::save_cards(&list));
+    list
+}
+
+fn or_exit<T>(result: Result<T, Error>) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(1);
+    })
+}
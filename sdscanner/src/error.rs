@@ -0,0 +1,135 @@
+use crate::scanning::format_size;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Everything that can go wrong in the library
+///
+/// The sources are kept behind an `Arc` so errors can be cloned into the GUI's messages
+#[derive(Debug, Clone)]
+pub enum Error {
+    /// A program the library relies on, like lsblk, couldn't be run or failed
+    Command {
+        program: &'static str,
+        message: String,
+    },
+    /// A file or folder couldn't be opened, read, written or removed
+    Io {
+        action: &'static str,
+        path: PathBuf,
+        source: Arc<io::Error>,
+    },
+    /// The saved card list couldn't be read or written as JSON
+    Json {
+        path: PathBuf,
+        source: Arc<serde_json::Error>,
+    },
+    /// A Valve KeyValues file is broken, the path is filled in once it's known
+    Vdf {
+        path: Option<PathBuf>,
+        message: String,
+    },
+    /// A folder's name isn't UTF-8 so it can't be shown or saved, it's skipped
+    NotUtf8(PathBuf),
+    /// There's no config folder to save the card list in
+    NoConfigDir,
+    /// Steam hasn't been logged into on this device
+    NoSteamUser,
+    /// Steam has to be closed first, it would overwrite or be confused by the change
+    SteamRunning { doing: &'static str },
+    /// There isn't room on the destination, both are in bytes
+    NotEnoughSpace { needed: u64, free: u64 },
+    /// The request can't be carried out, the message says why
+    Invalid(String),
+}
+
+impl Error {
+    /// For `map_err`, e.g. `.map_err(Error::io("read", &path))`
+    pub fn io<'a>(action: &'static str, path: &'a Path) -> impl FnOnce(io::Error) -> Error + 'a {
+        move |source| Error::Io {
+            action,
+            path: path.to_path_buf(),
+            source: Arc::new(source),
+        }
+    }
+
+    pub fn json(path: &Path) -> impl FnOnce(serde_json::Error) -> Error + '_ {
+        move |source| Error::Json {
+            path: path.to_path_buf(),
+            source: Arc::new(source),
+        }
+    }
+
+    pub fn vdf(message: impl Into<String>) -> Error {
+        Error::Vdf {
+            path: None,
+            message: message.into(),
+        }
+    }
+
+    /// Adds the file a VDF error came from
+    pub fn in_file(self, file: &Path) -> Error {
+        match self {
+            Error::Vdf {
+                path: None,
+                message,
+            } => Error::Vdf {
+                path: Some(file.to_path_buf()),
+                message,
+            },
+            error => error,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Command { program, message } => write!(f, "Couldn't run {program}: {message}"),
+            Error::Io {
+                action,
+                path,
+                source,
+            } => write!(f, "Couldn't {action} {}: {source}", path.display()),
+            Error::Json { path, source } => {
+                write!(f, "Couldn't parse {}: {source}", path.display())
+            }
+            Error::Vdf {
+                path: Some(path),
+                message,
+            } => write!(f, "Couldn't read {}: {message}", path.display()),
+            Error::Vdf {
+                path: None,
+                message,
+            } => f.write_str(message),
+            Error::NotUtf8(path) => write!(
+                f,
+                "Skipped {}, its name has characters that aren't UTF-8",
+                path.display()
+            ),
+            Error::NoConfigDir => f.write_str("Couldn't find the config folder"),
+            Error::NoSteamUser => f.write_str("Couldn't find a Steam user on this device"),
+            Error::SteamRunning { doing } => {
+                write!(f, "Steam is running, close it before {doing}")
+            }
+            Error::NotEnoughSpace { needed, free } => write!(
+                f,
+                "Not enough space, {} more is needed but only {} is free",
+                format_size(*needed),
+                format_size(*free)
+            ),
+            Error::Invalid(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source.as_ref()),
+            Error::Json { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,332 @@
+use crate::error::Error;
+use crate::scanning::{Card, ScanData};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The file written to a card's root when marker files are turned on in the settings
+pub const MARKER_FILE: &str = ".sdscanner-id";
+
+/// The sysfs folder of the whole card for a partition like `mmcblk0p1`
+fn card_sysfs_dir(partition: &str) -> Option<PathBuf> {
+    // /sys/class/block/mmcblk0p1 links into the card's own folder, .../block/mmcblk0/mmcblk0p1
+    let partition_dir = fs::canonicalize(Path::new("/sys/class/block").join(partition)).ok()?;
+    Some(partition_dir.parent()?.to_path_buf())
+}
+
+/// Reads one of the card's hardware registers from sysfs, e.g. `cid` or `serial`
+pub fn read_device_file(partition: &str, file: &str) -> Option<String> {
+    let contents = fs::read_to_string(card_sysfs_dir(partition)?.join("device").join(file)).ok()?;
+
+    match contents.trim() {
+        "" => None,
+        value => Some(value.to_string()),
+    }
+}
+
+/// The card's hardware ID, its CID register which holds the maker, product name, serial number and date.
+/// Stays the same when the card is reformatted and differs between cloned cards.
+/// None for cards in USB readers, which don't pass the registers through
+pub fn read_hardware_id(partition: &str) -> Option<String> {
+    if let Some(cid) = read_device_file(partition, "cid") {
+        return Some(cid.to_lowercase());
+    }
+
+    // Some hosts only have the separate fields
+    let fields: Vec<String> = ["manfid", "name", "serial", "date"]
+        .iter()
+        .filter_map(|file| read_device_file(partition, file))
+        .collect();
+    if fields.is_empty() {
+        None
+    } else {
+        Some(fields.join(":").to_lowercase())
+    }
+}
+
+/// Reads the ID stored in the card's marker file, if it has one
+pub fn read_marker(card_path: &Path) -> Option<String> {
+    let contents = fs::read_to_string(card_path.join(MARKER_FILE)).ok()?;
+
+    match contents.trim() {
+        "" => None,
+        id => Some(id.to_string()),
+    }
+}
+
+/// Writes a new marker file to the card's root, returns the new ID
+pub fn write_marker(card_path: &Path, uuid: &str) -> Result<String, Error> {
+    let mut hasher = DefaultHasher::new();
+    uuid.hash(&mut hasher);
+    SystemTime::now().hash(&mut hasher);
+    std::process::id().hash(&mut hasher);
+    let id = format!("{:016x}", hasher.finish());
+
+    let path = card_path.join(MARKER_FILE);
+    fs::write(&path, format!("{id}\n")).map_err(Error::io("write", &path))?;
+    Ok(id)
+}
+
+/// Finds the saved card that was scanned, returns its index in the list.
+/// The hardware ID is checked first so a reformatted card is still recognised, then the marker file,
+/// then the filesystem UUID. The last two only match if the saved card's hardware ID isn't a different one,
+/// since a cloned card has the same UUID and marker file as the original
+pub fn find_card(list: &[Card], scanned: &ScanData) -> Option<usize> {
+    let same_hardware = |card: &Card| match (&card.hardware_id, &scanned.hardware_id) {
+        (Some(saved), Some(scanned)) => saved == scanned,
+        _ => true,
+    };
+
+    if let Some(hardware_id) = &scanned.hardware_id {
+        if let Some(index) = list
+            .iter()
+            .position(|card| card.hardware_id.as_ref() == Some(hardware_id))
+        {
+            return Some(index);
+        }
+    }
+
+    if let Some(marker_id) = &scanned.marker_id {
+        if let Some(index) = list
+            .iter()
+            .position(|card| card.marker_id.as_ref() == Some(marker_id) && same_hardware(card))
+        {
+            return Some(index);
+        }
+    }
+
+    list.iter()
+        .position(|card| card.uuid == scanned.uuid && same_hardware(card))
+}
+
+/// Picks the ID a scanned card is saved under. A card `find_card` recognised keeps the ID of its saved entry,
+/// even when a reformat gave it a new UUID. A new card gets its filesystem UUID unless another saved card already uses it,
+/// then the UUID gets the end of the hardware ID added, so cloned cards get their own entries
+pub fn unique_card_id(list: &[Card], scanned: &ScanData, current_id: Option<&str>) -> String {
+    if let Some(current_id) = current_id {
+        return current_id.to_string();
+    }
+
+    let taken = |id: &str| list.iter().any(|card| card.uuid == id);
+    if !taken(&scanned.uuid) {
+        return scanned.uuid.clone();
+    }
+
+    let suffix = scanned
+        .hardware_id
+        .as_ref()
+        .or(scanned.marker_id.as_ref())
+        .map(|id| {
+            let chars: Vec<char> = id.chars().collect();
+            chars[chars.len().saturating_sub(8)..]
+                .iter()
+                .collect::<String>()
+        })
+        .unwrap_or_default();
+    let mut id = format!("{}-{suffix}", scanned.uuid);
+    let mut number = 2;
+    while taken(&id) {
+        id = format!("{}-{suffix}{number}", scanned.uuid);
+        number += 1;
+    }
+
+    id
+}
+
+/// What the card reports about itself through sysfs, read while scanning in the Deck's own card reader
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct CardHardware {
+    /// Manufacturer ID, e.g. `0x000003`
+    pub manfid: Option<String>,
+    /// OEM/application ID, e.g. `0x5344`
+    pub oemid: Option<String>,
+    /// The product name the maker programmed into the card
+    pub name: Option<String>,
+    /// Manufacturing date, `MM/YYYY`
+    pub date: Option<String>,
+    /// The raw card specific data register
+    pub csd: Option<String>,
+    /// The raw SD configuration register
+    pub scr: Option<String>,
+    /// The raw SD status register, holds the speed classes. Only on newer kernels
+    pub ssr: Option<String>,
+    /// The two wear estimates, e.g. `0x01 0x02`. Only reported by some cards
+    pub life_time: Option<String>,
+    /// The pre end of life warning, e.g. `0x01`. Only reported by some cards
+    pub pre_eol_info: Option<String>,
+}
+
+impl CardHardware {
+    /// Reads the registers of the card the partition is on, None if none of them could be read
+    pub fn read(partition: &str) -> Option<CardHardware> {
+        let read = |file: &str| read_device_file(partition, file);
+
+        let hardware = CardHardware {
+            manfid: read("manfid"),
+            oemid: read("oemid"),
+            name: read("name"),
+            date: read("date"),
+            csd: read("csd"),
+            scr: read("scr"),
+            ssr: read("ssr"),
+            life_time: read("life_time"),
+            pre_eol_info: read("pre_eol_info"),
+        };
+
+        if hardware == CardHardware::default() {
+            None
+        } else {
+            Some(hardware)
+        }
+    }
+
+    /// The maker's name for the well known manufacturer IDs, otherwise the ID itself
+    pub fn manufacturer(&self) -> Option<String> {
+        let manfid = self.manfid.as_ref()?;
+        let known = match parse_hex(manfid)? {
+            0x01 => "Panasonic",
+            0x02 => "Toshiba / Kioxia",
+            0x03 => "SanDisk",
+            0x1b => "Samsung",
+            0x1d => "ADATA",
+            0x27 => "Phison",
+            0x28 => "Lexar",
+            0x31 => "Silicon Power",
+            0x41 => "Kingston",
+            0x74 => "Transcend",
+            0x76 => "Patriot",
+            0x82 => "Sony",
+            _ => return Some(format!("Unknown ({manfid})")),
+        };
+
+        Some(known.to_string())
+    }
+
+    /// The OEM ID as the two letters it usually spells, e.g. `SD`
+    pub fn oem(&self) -> Option<String> {
+        let oemid = self.oemid.as_ref()?;
+        let value = parse_hex(oemid)?;
+        let letters: String = [(value >> 8) as u8, value as u8]
+            .iter()
+            .map(|byte| *byte as char)
+            .collect();
+
+        if letters.chars().all(|c| c.is_ascii_graphic()) {
+            Some(letters)
+        } else {
+            Some(oemid.clone())
+        }
+    }
+
+    /// The SD spec version the card follows, from the SCR register
+    pub fn spec_version(&self) -> Option<&'static str> {
+        let scr = hex_bytes(self.scr.as_ref()?)?;
+        let sd_spec = *scr.first()? & 0x0f;
+        let sd_spec3 = scr.get(1)? & 0x80 != 0;
+        let sd_spec4 = scr.get(1)? & 0x04 != 0;
+        let sd_specx = ((scr.get(1)? & 0x03) << 2) | (scr.get(2)? >> 6);
+
+        Some(match (sd_spec, sd_spec3, sd_spec4, sd_specx) {
+            (0, _, _, _) => "SD 1.0",
+            (1, _, _, _) => "SD 1.1",
+            (2, false, _, _) => "SD 2.0",
+            (2, true, _, 1) => "SD 5.x",
+            (2, true, _, 2) => "SD 6.x",
+            (2, true, _, 3) => "SD 7.x",
+            (2, true, _, 4) => "SD 8.x",
+            (2, true, _, 5) => "SD 9.x",
+            (2, true, true, _) => "SD 4.x",
+            (2, true, false, _) => "SD 3.0",
+            _ => "Unknown",
+        })
+    }
+
+    /// The speed ratings from the SD status register, e.g. `Class 10, U3, V30, A2`
+    pub fn speed_class(&self) -> Option<String> {
+        let ssr = hex_bytes(self.ssr.as_ref()?)?;
+        let mut ratings = vec![];
+
+        match ssr.get(8)? {
+            0 => ratings.push(String::from("Class 0")),
+            1 => ratings.push(String::from("Class 2")),
+            2 => ratings.push(String::from("Class 4")),
+            3 => ratings.push(String::from("Class 6")),
+            4 => ratings.push(String::from("Class 10")),
+            _ => {}
+        }
+        match ssr.get(14)? >> 4 {
+            0 => {}
+            grade => ratings.push(format!("U{grade}")),
+        }
+        match ssr.get(15)? {
+            0 => {}
+            video => ratings.push(format!("V{video}")),
+        }
+        match ssr.get(21)? & 0x0f {
+            0 => {}
+            performance => ratings.push(format!("A{performance}")),
+        }
+
+        if ratings.is_empty() {
+            None
+        } else {
+            Some(ratings.join(", "))
+        }
+    }
+
+    /// How worn out the card says it is, None if it doesn't report it.
+    /// Either the wear estimate or the pre end of life warning is enough, some cards only report one
+    pub fn health(&self) -> Option<String> {
+        let warning = match self.pre_eol_info.as_deref().and_then(parse_hex) {
+            Some(2) => Some("Warning, most of the spare blocks are used"),
+            Some(3) => Some("Urgent, almost out of spare blocks"),
+            _ => None,
+        };
+
+        // Each estimate goes from 0x01 for 0-10% of its life used up to 0x0b for past its expected life
+        let worst = self
+            .life_time
+            .as_deref()
+            .and_then(|life_time| life_time.split_whitespace().filter_map(parse_hex).max());
+        let used = match worst {
+            None | Some(0) => None,
+            Some(worst @ 1..=10) => {
+                Some(format!("Life used: {}-{}%", (worst - 1) * 10, worst * 10))
+            }
+            Some(_) => Some(String::from("Life used: past its expected life")),
+        };
+
+        match (used, warning) {
+            (Some(used), Some(warning)) => Some(format!("{used}. {warning}")),
+            (Some(used), None) => Some(used),
+            (None, Some(warning)) => Some(warning.to_string()),
+            (None, None) => None,
+        }
+    }
+}
+
+fn parse_hex(value: &str) -> Option<u32> {
+    u32::from_str_radix(value.trim().trim_start_matches("0x"), 16).ok()
+}
+
+/// Turns a register printed as hex into its bytes, most significant first
+fn hex_bytes(register: &str) -> Option<Vec<u8>> {
+    let register = register.trim();
+    if !register.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..register.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(register.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,132 @@
+use super::*;
+use crate::scanning::{self, Game};
+
+const HARDWARE_ID: &str = "035344534331323880a1b2c3d4014700";
+
+fn saved_card(uuid: &str, hardware_id: Option<&str>) -> Card {
+    Card {
+        uuid: uuid.to_string(),
+        name: String::from("Main card"),
+        games: vec![Game {
+            name: String::from("ELDEN RING"),
+            favourite: true,
+            note: String::from("Finish the DLC"),
+            ..Default::default()
+        }],
+        heroic: None,
+        lutris: None,
+        capacity: Some(512_000_000_000),
+        free_space: None,
+        mount_path: PathBuf::new(),
+        archived: true,
+        hardware_id: hardware_id.map(String::from),
+        marker_id: None,
+        hardware: None,
+        purchase_note: String::from("bought 2023"),
+        benchmarks: vec![],
+    }
+}
+
+fn inserted_card(uuid: &str, hardware_id: Option<&str>) -> ScanData {
+    ScanData {
+        card_path: PathBuf::from("/run/media/deck/card"),
+        uuid: uuid.to_string(),
+        name: None,
+        capacity: Some(512_000_000_000),
+        hardware_id: hardware_id.map(String::from),
+        marker_id: None,
+        hardware: None,
+    }
+}
+
+/// What `scan_card` returns for the inserted card, before the saved entry's user data is put back
+fn scanned_card(uuid: &str, hardware_id: Option<&str>) -> Card {
+    Card {
+        name: String::from("SD Card 1"),
+        games: vec![Game {
+            name: String::from("ELDEN RING"),
+            ..Default::default()
+        }],
+        archived: false,
+        purchase_note: String::new(),
+        ..saved_card(uuid, hardware_id)
+    }
+}
+
+#[test]
+fn a_reformatted_card_keeps_its_saved_entry() {
+    let mut list = vec![saved_card("OLD-UUID", Some(HARDWARE_ID))];
+    let inserted = inserted_card("NEW-UUID", Some(HARDWARE_ID));
+
+    let saved = find_card(&list, &inserted).map(|index| list[index].uuid.clone());
+    assert_eq!(saved.as_deref(), Some("OLD-UUID"));
+    let uuid = unique_card_id(&list, &inserted, saved.as_deref());
+    assert_eq!(uuid, "OLD-UUID");
+
+    scanning::add_scanned_cards(
+        &mut list,
+        vec![scanned_card(&uuid, Some(HARDWARE_ID))],
+        &mut vec![],
+    );
+
+    assert_eq!(list.len(), 1);
+    let card = &list[0];
+    assert_eq!(card.name, "Main card");
+    assert!(card.archived);
+    assert_eq!(card.purchase_note, "bought 2023");
+    assert!(card.games[0].favourite);
+    assert_eq!(card.games[0].note, "Finish the DLC");
+}
+
+#[test]
+fn a_cloned_card_gets_its_own_entry() {
+    let list = vec![saved_card("SAME-UUID", Some(HARDWARE_ID))];
+    let clone = inserted_card("SAME-UUID", Some("035344534331323880ffffffff014700"));
+
+    assert_eq!(find_card(&list, &clone), None);
+    assert_eq!(unique_card_id(&list, &clone, None), "SAME-UUID-ff014700");
+}
+
+#[test]
+fn a_new_card_is_saved_under_its_uuid() {
+    let list = vec![saved_card("OLD-UUID", Some(HARDWARE_ID))];
+    let inserted = inserted_card("NEW-UUID", None);
+
+    assert_eq!(find_card(&list, &inserted), None);
+    assert_eq!(unique_card_id(&list, &inserted, None), "NEW-UUID");
+}
+
+fn health(life_time: Option<&str>, pre_eol_info: Option<&str>) -> Option<String> {
+    CardHardware {
+        life_time: life_time.map(String::from),
+        pre_eol_info: pre_eol_info.map(String::from),
+        ..Default::default()
+    }
+    .health()
+}
+
+#[test]
+fn health_reports_the_worst_wear_estimate() {
+    assert_eq!(
+        health(Some("0x01 0x03"), Some("0x01")).as_deref(),
+        Some("Life used: 20-30%")
+    );
+    assert_eq!(
+        health(Some("0x0b 0x02"), Some("0x03")).as_deref(),
+        Some("Life used: past its expected life. Urgent, almost out of spare blocks")
+    );
+}
+
+#[test]
+fn health_keeps_the_pre_eol_warning_without_a_wear_estimate() {
+    assert_eq!(
+        health(None, Some("0x03")).as_deref(),
+        Some("Urgent, almost out of spare blocks")
+    );
+    assert_eq!(
+        health(Some("0x00 0x00"), Some("0x02")).as_deref(),
+        Some("Warning, most of the spare blocks are used")
+    );
+    assert_eq!(health(None, Some("0x01")), None);
+    assert_eq!(health(None, None), None);
+}
@@ -0,0 +1,236 @@
+use crate::error::Error;
+use crate::scanning::{self, card_games_count, Card, Game, OtherLibrary};
+use qrcode::{Color, EcLevel, QrCode};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+/// Every QR payload starts with this so an import can tell it apart from any other text
+pub const PAYLOAD_PREFIX: &str = "SDSCANNER:";
+
+/// Label size in SVG units, 10 units per millimeter for a 60mm x 30mm sticker
+const LABEL_WIDTH: usize = 600;
+const LABEL_HEIGHT: usize = 300;
+const LABEL_MARGIN: usize = 10;
+
+/// The data stored in a label's QR code, kept small so the game list of a full card still fits
+#[derive(Serialize, Deserialize)]
+pub struct LabelPayload {
+    pub uuid: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<u64>,
+    pub games: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lutris: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub heroic: Vec<String>,
+}
+
+impl From<&Card> for LabelPayload {
+    fn from(card: &Card) -> Self {
+        let names = |games: &Vec<Game>| games.iter().map(|game| game.name.clone()).collect();
+
+        LabelPayload {
+            uuid: card.uuid.clone(),
+            name: card.name.clone(),
+            capacity: card.capacity,
+            games: names(&card.games),
+            lutris: card.lutris.as_ref().map_or(vec![], |lib| names(&lib.games)),
+            heroic: card.heroic.as_ref().map_or(vec![], |lib| names(&lib.games)),
+        }
+    }
+}
+
+impl From<LabelPayload> for Card {
+    fn from(payload: LabelPayload) -> Self {
+        // The library paths aren't stored in the label, they get filled in when the card is scanned on this Deck
+        let library = |games: Vec<String>| {
+            if games.is_empty() {
+                None
+            } else {
+                Some(OtherLibrary {
+                    path: PathBuf::new(),
+                    games: games.into_iter().map(game_from_name).collect(),
+                })
+            }
+        };
+
+        Card {
+            uuid: payload.uuid,
+            name: payload.name,
+            games: payload.games.into_iter().map(game_from_name).collect(),
+            lutris: library(payload.lutris),
+            heroic: library(payload.heroic),
+            capacity: payload.capacity,
+            free_space: None,
+            mount_path: PathBuf::new(),
+            archived: false,
+            hardware_id: None,
+            marker_id: None,
+            hardware: None,
+            purchase_note: String::new(),
+            benchmarks: vec![],
+        }
+    }
+}
+
+/// Returns the text encoded into the card's QR code
+pub fn create_payload(card: &Card) -> Result<String, Error> {
+    let json = serde_json::to_string(&LabelPayload::from(card))
+        .map_err(|e| Error::Invalid(format!("Couldn't encode {}'s label: {e}", card.name)))?;
+    Ok(format!("{PAYLOAD_PREFIX}{json}"))
+}
+
+/// Builds the SVG sticker for the card, fails if the game list is too big to fit in a QR code
+pub fn create_label_svg(card: &Card) -> Result<String, Error> {
+    let code =
+        QrCode::with_error_correction_level(create_payload(card)?, EcLevel::L).map_err(|e| {
+            Error::Invalid(format!(
+                "Couldn't create a QR code for card {}: {e}",
+                card.name
+            ))
+        })?;
+
+    // The QR code fills the right side of the label as a square, leaving room for a 2 module quiet zone
+    let qr_size = LABEL_HEIGHT - LABEL_MARGIN * 2;
+    let module = qr_size as f32 / (code.width() + 4) as f32;
+    let qr_x = (LABEL_WIDTH - LABEL_MARGIN - qr_size) as f32 + module * 2.0;
+    let qr_y = LABEL_MARGIN as f32 + module * 2.0;
+
+    let mut qr_path = String::new();
+    for (i, color) in code.to_colors().into_iter().enumerate() {
+        if color == Color::Dark {
+            let x = qr_x + (i % code.width()) as f32 * module;
+            let y = qr_y + (i / code.width()) as f32 * module;
+            qr_path.push_str(&format!(
+                "M{x:.2},{y:.2}h{module:.2}v{module:.2}h-{module:.2}z"
+            ));
+        }
+    }
+
+    let capacity = match card.capacity {
+        Some(bytes) => scanning::format_size(bytes),
+        None => String::from("Unknown"),
+    };
+
+    let lines = [
+        format!(
+            r#"<text x="{LABEL_MARGIN}" y="60" font-size="44" font-weight="bold">{}</text>"#,
+            escape_xml(&card.name)
+        ),
+        format!(
+            r#"<text x="{LABEL_MARGIN}" y="130" font-size="32">ID: {}</text>"#,
+            escape_xml(short_uuid(&card.uuid))
+        ),
+        format!(r#"<text x="{LABEL_MARGIN}" y="190" font-size="32">Size: {capacity}</text>"#),
+        format!(
+            r#"<text x="{LABEL_MARGIN}" y="250" font-size="32">Games: {}</text>"#,
+            card_games_count(card)
+        ),
+    ];
+
+    Ok(format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="60mm" height="30mm" viewBox="0 0 {w} {h}" font-family="sans-serif">"#,
+            r#"<rect width="{w}" height="{h}" fill="white"/>"#,
+            r#"<g fill="black">{lines}</g>"#,
+            r#"<path fill="black" d="{qr}"/>"#,
+            "</svg>\n"
+        ),
+        w = LABEL_WIDTH,
+        h = LABEL_HEIGHT,
+        lines = lines.join(""),
+        qr = qr_path,
+    ))
+}
+
+/// Writes the card's label into ~/Documents/sdscanner-labels, returns the path of the saved file
+pub fn save_label(card: &Card) -> Result<PathBuf, Error> {
+    let svg = create_label_svg(card)?;
+
+    let label_dir = dirs::document_dir()
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| Error::Invalid(String::from("Couldn't find the Documents folder")))?
+        .join("sdscanner-labels");
+    fs::create_dir_all(&label_dir).map_err(Error::io("create", &label_dir))?;
+
+    let file_name: String = format!("{}-{}.svg", card.name, short_uuid(&card.uuid))
+        .chars()
+        .map(|c| if c == '/' { '_' } else { c })
+        .collect();
+    let label_path = label_dir.join(file_name);
+
+    fs::write(&label_path, svg).map_err(Error::io("save the label to", &label_path))?;
+    Ok(label_path)
+}
+
+/// Reads a scanned QR payload and adds the card it describes to the list.
+/// A card that is already known keeps its local name and games, only the games it's missing are added.
+/// Returns the name of the imported card, fails if the text isn't a label payload.
+pub fn import_payload(payload: &str, list: &mut Vec<Card>) -> Result<String, Error> {
+    let json = payload
+        .trim()
+        .strip_prefix(PAYLOAD_PREFIX)
+        .ok_or_else(|| Error::Invalid(String::from("That isn't a card label's QR code data")))?;
+
+    let imported: Card = serde_json::from_str::<LabelPayload>(json)
+        .map_err(|e| Error::Invalid(format!("Couldn't read the label data: {e}")))?
+        .into();
+
+    match list.iter_mut().find(|card| card.uuid == imported.uuid) {
+        Some(card) => {
+            merge_games(&mut card.games, imported.games);
+            merge_library(&mut card.lutris, imported.lutris);
+            merge_library(&mut card.heroic, imported.heroic);
+            card.capacity = card.capacity.or(imported.capacity);
+            Ok(card.name.clone())
+        }
+        None => {
+            let name = imported.name.clone();
+            list.push(imported);
+            Ok(name)
+        }
+    }
+}
+
+/// Adds the games the list doesn't have yet, the ones it has keep their favourites, notes and scan data
+fn merge_games(games: &mut Vec<Game>, imported: Vec<Game>) {
+    let mut added = false;
+    for game in imported {
+        if !games.iter().any(|existing| existing.name == game.name) {
+            games.push(game);
+            added = true;
+        }
+    }
+    if added {
+        games.sort_by_key(|game| game.name.to_ascii_lowercase());
+    }
+}
+
+/// Merges the label's games into the saved library, keeping the saved library's path
+fn merge_library(library: &mut Option<OtherLibrary>, imported: Option<OtherLibrary>) {
+    match (library, imported) {
+        (Some(library), Some(imported)) => merge_games(&mut library.games, imported.games),
+        (library @ None, imported) => *library = imported,
+        (Some(_), None) => {}
+    }
+}
+
+fn game_from_name(name: String) -> Game {
+    Game {
+        name,
+        ..Default::default()
+    }
+}
+
+/// The first 4 characters of the UUID, the same short ID shown on the settings page
+pub fn short_uuid(uuid: &str) -> &str {
+    uuid.get(..4).unwrap_or(uuid)
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
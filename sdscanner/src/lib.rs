@@ -0,0 +1,34 @@
+//! Finds the games on Steam Deck SD cards and keeps track of which card has which games.
+//!
+//! The usual flow is to find the inserted cards with [`discover_cards`], scan each one with [`scan_card`]
+//! (or update a saved list in one go with [`update_list`]), keep the list with [`load_cards`] and [`save_cards`],
+//! and search it with [`Query`]. Everything that can fail returns an [`Error`], nothing is printed
+//! except by the command line commands in `cli`, which need the `cli` feature.
+
+pub mod artwork;
+pub mod benchmark;
+pub mod cleanup;
+#[cfg(feature = "cli")]
+pub mod cli;
+mod error;
+pub mod identity;
+pub mod label;
+pub mod planner;
+pub mod preferences;
+pub mod scanning;
+pub mod search;
+pub mod shortcuts;
+pub mod snapshot;
+pub mod steam;
+pub mod transfer;
+pub mod uninstall;
+pub mod vdf;
+pub mod verify;
+
+pub use error::Error;
+pub use preferences::Preferences;
+pub use scanning::{
+    discover_cards, load_cards, save_cards, scan_card, update_list, Card, Game, Library,
+    OtherLibrary, ScanData,
+};
+pub use search::Query;
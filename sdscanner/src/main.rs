@@ -0,0 +1,14 @@
+use sdscanner::{cli, preferences};
+
+fn main() {
+    let preferences = preferences::load().unwrap_or_else(|e| {
+        eprintln!("{e}, using the default settings");
+        Default::default()
+    });
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !cli::run(&args, &preferences) {
+        eprintln!("{}", cli::USAGE);
+        std::process::exit(2);
+    }
+}
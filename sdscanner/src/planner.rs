@@ -0,0 +1,205 @@
+use crate::error::Error;
+use crate::scanning::{self, Card, Game, Library};
+use crate::search;
+use std::collections::HashMap;
+
+/// How many games are suggested for removal on each card that's too full
+const MAX_CANDIDATES: usize = 5;
+
+/// How much space the game being planned for needs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requirement {
+    pub bytes: u64,
+    /// The game's name if the size came from an installed copy
+    pub game: Option<String>,
+}
+
+/// Reads a size like `45G`, or a Steam app ID of a game installed on one of the cards to use its size
+pub fn parse_requirement(input: &str, list: &[Card]) -> Result<Requirement, Error> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(Error::Invalid(String::from(
+            "Type the game's size, e.g. 45G, or its Steam app ID",
+        )));
+    }
+
+    if input.chars().all(|c| c.is_ascii_digit()) {
+        let appid: u32 = input
+            .parse()
+            .map_err(|_| Error::Invalid(format!("{input} isn't a Steam app ID")))?;
+        let game = list
+            .iter()
+            .flat_map(|card| &card.games)
+            .find(|game| game.appid == Some(appid))
+            .ok_or_else(|| {
+                Error::Invalid(format!(
+                    "App ID {appid} isn't installed on any saved card, type its size instead"
+                ))
+            })?;
+        return game_requirement(game);
+    }
+
+    match search::parse_size(input) {
+        Some((bytes, _)) => Ok(Requirement { bytes, game: None }),
+        None => Err(Error::Invalid(format!(
+            "Couldn't read {input} as a size, try something like 45G"
+        ))),
+    }
+}
+
+/// The space an installed game takes up, to plan a copy of it on another card
+pub fn game_requirement(game: &Game) -> Result<Requirement, Error> {
+    match game.size {
+        Some(bytes) => Ok(Requirement {
+            bytes,
+            game: Some(game.name.clone()),
+        }),
+        None => Err(Error::Invalid(format!(
+            "{}'s size isn't known, turn on Add Up Game Sizes and rescan its card",
+            game.name
+        ))),
+    }
+}
+
+/// A card the game could go on
+#[derive(Debug, Clone)]
+pub struct Placement {
+    pub card_uuid: String,
+    pub card_name: String,
+    /// The card's free space, None if it was never measured
+    pub free: Option<u64>,
+    pub connected: bool,
+    /// Games that could be removed to make room, empty if the game already fits
+    pub candidates: Vec<Candidate>,
+}
+
+impl Placement {
+    pub fn fits(&self, required: u64) -> bool {
+        self.free.is_some_and(|free| free >= required)
+    }
+}
+
+/// A game suggested for removal and why
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub library: Library,
+    pub game: String,
+    pub size: u64,
+    pub reasons: Vec<String>,
+}
+
+/// Ranks every card by how much space it has left after the install, cards that fit first.
+/// `connected` has (UUID, mount path) of the inserted cards, their free space is measured now instead of using the saved one
+pub fn plan(
+    list: &[Card],
+    required: u64,
+    connected: &HashMap<String, std::path::PathBuf>,
+) -> Vec<Placement> {
+    let mut placements: Vec<Placement> = list
+        .iter()
+        .filter(|card| !card.archived)
+        .map(|card| {
+            let mount_path = connected.get(&card.uuid);
+            let free = mount_path
+                .and_then(|path| scanning::free_space(path).ok())
+                .or(card.free_space);
+
+            let mut placement = Placement {
+                card_uuid: card.uuid.clone(),
+                card_name: card.name.clone(),
+                free,
+                connected: mount_path.is_some(),
+                candidates: vec![],
+            };
+            if let (false, Some(free)) = (placement.fits(required), free) {
+                placement.candidates = removal_candidates(list, card, required - free);
+            }
+            placement
+        })
+        .collect();
+
+    // Cards that were never measured go last
+    placements.sort_by_key(|placement| {
+        (
+            placement.free.is_none(),
+            std::cmp::Reverse(placement.free.map(|free| free as i128 - required as i128)),
+        )
+    });
+    placements
+}
+
+/// Picks games on the card to remove until `needed` bytes are freed. Games that are on another card too go first,
+/// then the ones played least recently, then the biggest
+fn removal_candidates(list: &[Card], card: &Card, needed: u64) -> Vec<Candidate> {
+    let elsewhere = |game: &Game| -> Option<&str> {
+        list.iter()
+            .filter(|other| other.uuid != card.uuid)
+            .find(|other| {
+                other
+                    .games
+                    .iter()
+                    .any(|other_game| match (other_game.appid, game.appid) {
+                        (Some(a), Some(b)) => a == b,
+                        _ => other_game.name == game.name,
+                    })
+            })
+            .map(|other| other.name.as_str())
+    };
+
+    let mut games: Vec<(Library, &Game)> = [Library::Steam, Library::Lutris, Library::Heroic]
+        .into_iter()
+        .flat_map(|library| {
+            card.library_games(library)
+                .iter()
+                .map(move |game| (library, game))
+        })
+        .filter(|(_, game)| game.size.is_some_and(|size| size > 0) && !game.favourite)
+        .collect();
+
+    let biggest = games
+        .iter()
+        .filter_map(|(_, game)| game.size)
+        .max()
+        .unwrap_or(0);
+    games.sort_by_key(|(_, game)| {
+        (
+            elsewhere(game).is_none(),
+            game.last_played.unwrap_or(0),
+            std::cmp::Reverse(game.size),
+        )
+    });
+
+    let mut candidates = vec![];
+    let mut freed = 0;
+    for (library, game) in games {
+        if freed >= needed || candidates.len() >= MAX_CANDIDATES {
+            break;
+        }
+        let size = game.size.unwrap_or(0);
+
+        let mut reasons = vec![];
+        if let Some(other) = elsewhere(game) {
+            reasons.push(format!("Also on {other}"));
+        }
+        match game.last_played {
+            Some(last_played) => reasons.push(format!(
+                "Last played {}",
+                scanning::format_date(last_played)
+            )),
+            None => reasons.push(String::from("Never played on this Deck")),
+        }
+        if size == biggest {
+            reasons.push(String::from("Biggest game on the card"));
+        }
+
+        freed += size;
+        candidates.push(Candidate {
+            library,
+            game: game.name.clone(),
+            size,
+            reasons,
+        });
+    }
+
+    candidates
+}
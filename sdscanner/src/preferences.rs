@@ -0,0 +1,283 @@
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Component, PathBuf},
+};
+
+/// The user's settings, saved separately from the card data in ~/.config/sdscannerprefs.json
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Preferences {
+    /// Look for a Lutris library on the cards
+    pub scan_lutris: bool,
+    /// Look for a Heroic library on the cards
+    pub scan_heroic: bool,
+    /// Extra folders, relative to a card's root, to look for Lutris and Heroic libraries in
+    pub extra_search_roots: Vec<PathBuf>,
+    /// Add up the size of every game folder while scanning, slow on big cards
+    pub compute_sizes: bool,
+    /// Record every game's files while scanning, to show what changed since the last scan
+    pub deep_scan: DeepScan,
+    /// Rescan as soon as a card is inserted
+    pub auto_rescan: bool,
+    /// Write a small ID file to each card's root, helps recognise cards in USB readers that hide the hardware ID
+    pub write_marker_file: bool,
+    pub default_view: ListView,
+    pub default_sort: SortBy,
+    pub theme: ThemeChoice,
+    /// How big everything is drawn, in percent
+    pub font_scale: u16,
+    pub window_size: WindowSize,
+    /// Start the app in fullscreen
+    pub fullscreen: bool,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Preferences {
+            scan_lutris: true,
+            scan_heroic: true,
+            extra_search_roots: vec![],
+            compute_sizes: true,
+            deep_scan: DeepScan::Off,
+            auto_rescan: false,
+            write_marker_file: false,
+            default_view: ListView::Cards,
+            default_sort: SortBy::Name,
+            theme: ThemeChoice::Dark,
+            font_scale: 100,
+            window_size: WindowSize::STEAM_DECK,
+            fullscreen: false,
+        }
+    }
+}
+
+impl Preferences {
+    pub fn apply(&mut self, change: PreferenceChange) {
+        match change {
+            PreferenceChange::ScanLutris(value) => self.scan_lutris = value,
+            PreferenceChange::ScanHeroic(value) => self.scan_heroic = value,
+            PreferenceChange::ComputeSizes(value) => self.compute_sizes = value,
+            PreferenceChange::DeepScan(deep_scan) => self.deep_scan = deep_scan,
+            PreferenceChange::AutoRescan(value) => self.auto_rescan = value,
+            PreferenceChange::WriteMarkerFile(value) => self.write_marker_file = value,
+            PreferenceChange::DefaultView(list_view) => self.default_view = list_view,
+            PreferenceChange::DefaultSort(sort_by) => self.default_sort = sort_by,
+            PreferenceChange::Theme(theme) => self.theme = theme,
+            PreferenceChange::FontScale(scale) => self.font_scale = scale,
+            PreferenceChange::WindowSize(size) => self.window_size = size,
+            PreferenceChange::Fullscreen(value) => self.fullscreen = value,
+            PreferenceChange::AddSearchRoot(path) => {
+                // Only keep the plain folder names so the root always stays inside the card
+                let path: PathBuf = path
+                    .components()
+                    .filter(|component| matches!(component, Component::Normal(_)))
+                    .collect();
+                if !path.as_os_str().is_empty() && !self.extra_search_roots.contains(&path) {
+                    self.extra_search_roots.push(path);
+                }
+            }
+            PreferenceChange::RemoveSearchRoot(index) => {
+                if index < self.extra_search_roots.len() {
+                    self.extra_search_roots.remove(index);
+                }
+            }
+        }
+    }
+}
+
+/// A change made on the settings page
+#[derive(Debug, Clone)]
+pub enum PreferenceChange {
+    ScanLutris(bool),
+    ScanHeroic(bool),
+    ComputeSizes(bool),
+    DeepScan(DeepScan),
+    AutoRescan(bool),
+    WriteMarkerFile(bool),
+    DefaultView(ListView),
+    DefaultSort(SortBy),
+    Theme(ThemeChoice),
+    FontScale(u16),
+    WindowSize(WindowSize),
+    Fullscreen(bool),
+    /// Add a folder to `extra_search_roots`
+    AddSearchRoot(PathBuf),
+    /// Remove the folder at the index from `extra_search_roots`
+    RemoveSearchRoot(usize),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThemeChoice {
+    Dark,
+    Light,
+}
+
+impl ThemeChoice {
+    pub const ALL: [ThemeChoice; 2] = [ThemeChoice::Dark, ThemeChoice::Light];
+}
+
+impl std::fmt::Display for ThemeChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ThemeChoice::Dark => "Dark",
+            ThemeChoice::Light => "Light",
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeepScan {
+    Off,
+    /// List each file's size and modified time
+    Files,
+    /// Also hash every file, catches changes that keep the size and time but reads the whole card
+    Hashes,
+}
+
+impl DeepScan {
+    pub const ALL: [DeepScan; 3] = [DeepScan::Off, DeepScan::Files, DeepScan::Hashes];
+}
+
+impl std::fmt::Display for DeepScan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            DeepScan::Off => "Off",
+            DeepScan::Files => "Files",
+            DeepScan::Hashes => "Files and Hashes",
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WindowSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl WindowSize {
+    /// The Steam Deck's screen
+    pub const STEAM_DECK: WindowSize = WindowSize {
+        width: 1280,
+        height: 800,
+    };
+
+    pub const ALL: [WindowSize; 4] = [
+        WindowSize::STEAM_DECK,
+        WindowSize {
+            width: 1024,
+            height: 640,
+        },
+        WindowSize {
+            width: 1600,
+            height: 1000,
+        },
+        WindowSize {
+            width: 1920,
+            height: 1200,
+        },
+    ];
+}
+
+impl std::fmt::Display for WindowSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+/// The font scale options shown on the settings page, in percent
+pub const FONT_SCALES: [u16; 6] = [75, 90, 100, 110, 125, 150];
+
+fn preferences_path() -> Result<PathBuf, Error> {
+    Ok(dirs::config_dir()
+        .ok_or(Error::NoConfigDir)?
+        .join("sdscannerprefs.json"))
+}
+
+/// Reads the preferences file, the defaults if it hasn't been saved yet
+pub fn load() -> Result<Preferences, Error> {
+    let path = preferences_path()?;
+    if !path.is_file() {
+        return Ok(Preferences::default());
+    }
+
+    let contents = fs::read_to_string(&path).map_err(Error::io("read", &path))?;
+    serde_json::from_str(&contents).map_err(Error::json(&path))
+}
+
+pub fn save(preferences: &Preferences) -> Result<(), Error> {
+    let path = preferences_path()?;
+    let s = serde_json::to_string_pretty(preferences).map_err(Error::json(&path))?;
+    fs::write(&path, s).map_err(Error::io("write", &path))
+}
+
+/// How the games on the list page are laid out
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListView {
+    /// Each card with its libraries and games under it
+    Cards,
+    /// One list of every game with a column for its card
+    AllGames,
+    /// The games grouped by Steam, Lutris and Heroic
+    Libraries,
+    /// Every game's cover art in rows
+    Grid,
+}
+
+impl ListView {
+    pub const ALL: [ListView; 4] = [
+        ListView::Cards,
+        ListView::AllGames,
+        ListView::Libraries,
+        ListView::Grid,
+    ];
+}
+
+impl std::fmt::Display for ListView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ListView::Cards => "By Card",
+            ListView::AllGames => "All Games",
+            ListView::Libraries => "By Library",
+            ListView::Grid => "Grid",
+        })
+    }
+}
+
+/// The order games are listed in
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortBy {
+    /// Alphabetical, or the best search matches first while searching
+    Name,
+    /// Biggest first
+    Size,
+    /// Most recently updated first
+    LastUpdated,
+    /// Most recently played first
+    LastPlayed,
+    /// Most played first
+    Playtime,
+}
+
+impl SortBy {
+    pub const ALL: [SortBy; 5] = [
+        SortBy::Name,
+        SortBy::Size,
+        SortBy::LastUpdated,
+        SortBy::LastPlayed,
+        SortBy::Playtime,
+    ];
+}
+
+impl std::fmt::Display for SortBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SortBy::Name => "Name",
+            SortBy::Size => "Size",
+            SortBy::LastUpdated => "Last Updated",
+            SortBy::LastPlayed => "Last Played",
+            SortBy::Playtime => "Playtime",
+        })
+    }
+}
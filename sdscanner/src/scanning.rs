@@ -0,0 +1,1025 @@
+use crate::benchmark::{self, BenchmarkResult};
+use crate::error::Error;
+use crate::identity::{self, CardHardware};
+use crate::preferences::{DeepScan, Preferences};
+use crate::snapshot;
+use crate::steam;
+use crate::vdf::AppManifest;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    str,
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, UNIX_EPOCH},
+};
+
+/// Struct used to organize all the SD Cards data
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Card {
+    /// The UUID of the card, used to identify and save the cards info
+    pub uuid: String,
+    /// The SD card's name, can be set by the user. Otherwise, it is named '
+    pub name: String,
+    /// The list of Steam Games
+    pub games: Vec<Game>,
+    /// The Heroic Games library if one was found
+    pub heroic: Option<OtherLibrary>,
+    /// The Lutris library if one was found
+    pub lutris: Option<OtherLibrary>,
+    /// The card's size in bytes as reported by lsblk, None for cards saved before it was tracked
+    #[serde(default)]
+    pub capacity: Option<u64>,
+    /// The free space on the card the last time it was scanned, in bytes
+    #[serde(default)]
+    pub free_space: Option<u64>,
+    /// Where the card was mounted the last time it was scanned
+    #[serde(default)]
+    pub mount_path: PathBuf,
+    /// Hidden from the list unless searching, set from the settings page
+    #[serde(default)]
+    pub archived: bool,
+    /// The card's hardware CID, see `identity::read_hardware_id`. None if it was only ever read through a USB reader
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hardware_id: Option<String>,
+    /// The ID from the card's marker file, if it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub marker_id: Option<String>,
+    /// The card's maker, model and registers, shown on the card info page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hardware: Option<CardHardware>,
+    /// Where and when the card was bought, written by the user
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub purchase_note: String,
+    /// The card's speed test results, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub benchmarks: Vec<BenchmarkResult>,
+}
+
+impl Card {
+    /// The games found in one of the card's libraries, empty if the card doesn't have that library
+    pub fn library_games(&self, library: Library) -> &[Game] {
+        match library {
+            Library::Steam => &self.games,
+            Library::Lutris => self.lutris.as_ref().map_or(&[], |lib| &lib.games),
+            Library::Heroic => self.heroic.as_ref().map_or(&[], |lib| &lib.games),
+        }
+    }
+
+    pub fn library_games_mut(&mut self, library: Library) -> Option<&mut Vec<Game>> {
+        match library {
+            Library::Steam => Some(&mut self.games),
+            Library::Lutris => self.lutris.as_mut().map(|lib| &mut lib.games),
+            Library::Heroic => self.heroic.as_mut().map(|lib| &mut lib.games),
+        }
+    }
+
+    /// Adds a speed test result, only keeping the last few
+    pub fn add_benchmark(&mut self, result: BenchmarkResult) {
+        self.benchmarks.push(result);
+        let extra = self
+            .benchmarks
+            .len()
+            .saturating_sub(benchmark::KEPT_RESULTS);
+        self.benchmarks.drain(..extra);
+    }
+
+    /// The full path of a game's folder, based on where the card was last mounted.
+    /// None if the library's location isn't known, like for cards imported from a label
+    pub fn game_path(&self, library: Library, game: &Game) -> Option<PathBuf> {
+        let library_path = match library {
+            Library::Steam => self.mount_path.join("steamapps/common"),
+            Library::Lutris => self.lutris.as_ref()?.path.clone(),
+            Library::Heroic => self.heroic.as_ref()?.path.clone(),
+        };
+
+        if library_path.is_relative() {
+            return None;
+        }
+
+        Some(library_path.join(&game.name))
+    }
+}
+
+/// Struct used to organize any data about a game found in a library folder
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Game {
+    /// The game's folder name
+    pub name: String,
+    /// Total size of the game's folder in bytes
+    #[serde(default)]
+    pub size: Option<u64>,
+    /// The Steam app ID, read from the library's appmanifest files. None for non Steam games
+    #[serde(default)]
+    pub appid: Option<u32>,
+    /// When the game was last updated as a unix timestamp, Steam's LastUpdated or the folder's modified time
+    #[serde(default)]
+    pub updated: Option<u64>,
+    /// Steam's build ID for the installed version
+    #[serde(default)]
+    pub build: Option<u32>,
+    /// Steam's StateFlags for the install, see `install_state`
+    #[serde(default)]
+    pub state_flags: Option<u32>,
+    /// When the game was last played on this Deck as a unix timestamp, from Steam's localconfig.vdf
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_played: Option<u64>,
+    /// Total minutes played, from Steam's localconfig.vdf
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub playtime: Option<u64>,
+    /// Marked as a favourite by the user
+    #[serde(default)]
+    pub favourite: bool,
+    /// A note written by the user
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+    /// What the last verify found wrong with the game's files, cleared by the next clean verify
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub problems: Vec<String>,
+}
+
+/// Describes Steam's StateFlags from an appmanifest
+pub fn install_state(state_flags: u32) -> &'static str {
+    // The flags, in order of importance, from Steam's EAppState
+    const STATES: [(u32, &str); 9] = [
+        (32, "Files Missing"),
+        (128, "Files Corrupt"),
+        (2048, "Uninstalling"),
+        (1048576, "Downloading"),
+        (256, "Updating"),
+        (512, "Update Paused"),
+        (1024, "Update Started"),
+        (2, "Update Required"),
+        (4, "Installed"),
+    ];
+
+    STATES
+        .iter()
+        .find(|(flag, _)| state_flags & flag != 0)
+        .map_or("Not Installed", |(_, state)| state)
+}
+
+/// The libraries a game can be found in
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Library {
+    Steam,
+    Lutris,
+    Heroic,
+}
+
+/// Struct for game other game libraries that aren't Steam, Lutris and Heroic for now
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct OtherLibrary {
+    /// The path of the libraries folder, will be used to scan the already found folder and skip searching for it again.
+    pub path: PathBuf,
+    /// List of games found in the library's folder
+    pub games: Vec<Game>,
+}
+
+/// Enum used to tell different functions what library to work on
+#[derive(Copy, Clone)]
+enum LibraryType {
+    Lutris,
+    Heroic,
+    Other,
+    Game,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScanData {
+    pub card_path: PathBuf,
+    pub uuid: String,
+    pub name: Option<String>,
+    pub capacity: Option<u64>,
+    pub hardware_id: Option<String>,
+    pub marker_id: Option<String>,
+    pub hardware: Option<CardHardware>,
+}
+
+/// What `scan_inserted_cards` found, put into the list with `add_scanned_cards`
+#[derive(Debug, Clone)]
+pub struct ScanResult {
+    /// The cards that were mounted when the scan ran
+    pub inserted: Vec<ScanData>,
+    /// The freshly scanned cards, a card already on the list has the UUID of its saved entry
+    pub cards: Vec<Card>,
+    /// Everything that went wrong without stopping the scan
+    pub warnings: Vec<Error>,
+}
+
+/// Modifies the passed in list with the currently inserted SD cards game data.
+/// Only fails if the inserted cards couldn't be listed, anything that went wrong after that is returned as warnings:
+/// a card that can't be scanned is left as it was, and folders that can't be read are skipped
+pub fn update_list(list: &mut Vec<Card>, preferences: &Preferences) -> Result<Vec<Error>, Error> {
+    let mut result = scan_inserted_cards(list, preferences)?;
+    add_scanned_cards(list, result.cards, &mut result.warnings);
+    Ok(result.warnings)
+}
+
+/// Scans every inserted card without changing the list, so it can run away from the UI while the list keeps being edited.
+/// This is the slow part of `update_list`, it reads every game folder and the deep scan reads every file
+pub fn scan_inserted_cards(list: &[Card], preferences: &Preferences) -> Result<ScanResult, Error> {
+    // Instead of checking if the card is on the list, just always update whatever info is saved for that card,
+    // Now the function can update the games list while scanning
+
+    let cards_to_scan: Vec<ScanData> = discover_cards()?;
+    let mut result = ScanResult {
+        inserted: cards_to_scan.clone(),
+        cards: vec![],
+        warnings: vec![],
+    };
+
+    for mut card_to_scan in cards_to_scan {
+        // For each SD card found in the lsblk scan
+        if preferences.write_marker_file && card_to_scan.marker_id.is_none() {
+            match identity::write_marker(&card_to_scan.card_path, &card_to_scan.uuid) {
+                Ok(marker_id) => card_to_scan.marker_id = Some(marker_id),
+                Err(e) => result.warnings.push(e),
+            }
+        }
+
+        // Check to see if this card was scanned before and is already on the saved list
+        let saved = identity::find_card(list, &card_to_scan).map(|index| &list[index]);
+        let data = ScanData {
+            uuid: identity::unique_card_id(
+                list,
+                &card_to_scan,
+                saved.map(|card| card.uuid.as_str()),
+            ),
+            name: saved.map(|card| card.name.clone()),
+            ..card_to_scan
+        };
+
+        match scan_card(data, preferences, &mut result.warnings) {
+            Ok(scanned_card) => result.cards.push(scanned_card),
+            Err(e) => result.warnings.push(e),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Puts the cards from `scan_inserted_cards` into the list. A card already on the list keeps its name,
+/// favourites, notes and the rest of what the user set, a new card is added to the end
+pub fn add_scanned_cards(list: &mut Vec<Card>, scanned: Vec<Card>, warnings: &mut Vec<Error>) {
+    for mut scanned_card in scanned {
+        match list.iter().position(|card| card.uuid == scanned_card.uuid) {
+            // The card was scanned before, update it with the new scanned info
+            Some(index) => {
+                let card = &list[index];
+                keep_user_game_data(card, &mut scanned_card);
+                // The card might have been renamed while it was being scanned
+                scanned_card.name = card.name.clone();
+                scanned_card.archived = card.archived;
+                // A USB reader doesn't show the hardware ID, keep the one found before
+                scanned_card.hardware_id = scanned_card.hardware_id.or(card.hardware_id.clone());
+                scanned_card.marker_id = scanned_card.marker_id.or(card.marker_id.clone());
+                scanned_card.hardware = scanned_card.hardware.or(card.hardware.clone());
+                scanned_card.purchase_note = card.purchase_note.clone();
+                scanned_card.benchmarks = card.benchmarks.clone();
+                list[index] = scanned_card
+            }
+            // If the current card isn't in the list, add it to the list
+            None => list.push(scanned_card),
+        }
+    }
+
+    // Every card's games, inserted or not, get the latest play data from this Deck
+    if let Err(e) = steam::attach_play_data(list) {
+        warnings.push(e);
+    }
+}
+
+/// Moves an old card entry into another one, used when a card was reformatted and came back with a new UUID.
+/// The new entry takes the old one's name and the favourites and notes of any games on both, then the old entry is removed.
+/// Returns false if either card isn't in the list
+pub fn merge_cards(list: &mut Vec<Card>, from_uuid: &str, into_uuid: &str) -> bool {
+    let from_index = match list.iter().position(|card| card.uuid == from_uuid) {
+        Some(index) if from_uuid != into_uuid => index,
+        _ => return false,
+    };
+    let into_index = match list.iter().position(|card| card.uuid == into_uuid) {
+        Some(index) => index,
+        None => return false,
+    };
+
+    let from = list.remove(from_index);
+    // Removing the old entry moves the ones after it up one
+    let into = &mut list[into_index - usize::from(into_index > from_index)];
+
+    into.name = from.name.clone();
+    into.capacity = into.capacity.or(from.capacity);
+    if into.purchase_note.is_empty() {
+        into.purchase_note = from.purchase_note.clone();
+    }
+    if into.benchmarks.is_empty() {
+        into.benchmarks = from.benchmarks.clone();
+    }
+    for library in [Library::Steam, Library::Lutris, Library::Heroic] {
+        let old_games = from.library_games(library);
+        if let Some(games) = into.library_games_mut(library) {
+            for game in games {
+                if let Some(old_game) = old_games.iter().find(|old| old.name == game.name) {
+                    game.favourite |= old_game.favourite;
+                    if game.note.is_empty() {
+                        game.note = old_game.note.clone();
+                    }
+                }
+            }
+        }
+    }
+
+    true
+}
+
+/// Takes back what `merge_cards` copied from `from` onto a card, `before` is the card as it was before the merge.
+/// Only values that are still the ones the merge put there are taken back, so edits and scans since are kept
+pub fn unmerge_card(card: &mut Card, before: &Card, from: &Card) {
+    if card.name == from.name {
+        card.name = before.name.clone();
+    }
+    if before.purchase_note.is_empty() && card.purchase_note == from.purchase_note {
+        card.purchase_note.clear();
+    }
+    if before.benchmarks.is_empty() {
+        // Tests run since the merge were added after the copied ones
+        let copied = from.benchmarks.len().min(card.benchmarks.len());
+        card.benchmarks.drain(..copied);
+    }
+    for library in [Library::Steam, Library::Lutris, Library::Heroic] {
+        let old_games = before.library_games(library);
+        let from_games = from.library_games(library);
+        if let Some(games) = card.library_games_mut(library) {
+            for game in games {
+                let old_game = old_games.iter().find(|old| old.name == game.name);
+                let from_game = match from_games.iter().find(|old| old.name == game.name) {
+                    Some(from_game) => from_game,
+                    None => continue,
+                };
+                if from_game.favourite && !old_game.is_some_and(|old| old.favourite) {
+                    game.favourite = false;
+                }
+                if old_game.is_none_or(|old| old.note.is_empty()) && game.note == from_game.note {
+                    game.note.clear();
+                }
+            }
+        }
+    }
+}
+
+/// Copies the favourites and notes the user set on the old card's games over to the freshly scanned card,
+/// along with any problems the last verify found and the old sizes if the new scan didn't add them up
+fn keep_user_game_data(old_card: &Card, scanned_card: &mut Card) {
+    for library in [Library::Steam, Library::Lutris, Library::Heroic] {
+        let old_games = old_card.library_games(library);
+        if let Some(games) = scanned_card.library_games_mut(library) {
+            for game in games {
+                if let Some(old_game) = old_games.iter().find(|old| old.name == game.name) {
+                    game.favourite = old_game.favourite;
+                    game.note = old_game.note.clone();
+                    game.problems = old_game.problems.clone();
+                    game.last_played = old_game.last_played;
+                    game.playtime = old_game.playtime;
+                    // Sizes aren't added up when that's turned off in the settings, keep the last known one
+                    game.size = game.size.or(old_game.size);
+                }
+            }
+        }
+    }
+}
+
+/// Get the data for the current card, the card's name gets decided from the passed in list.
+/// Fails if the card's Steam library can't be read, folders that can't be read inside it are skipped and added to `warnings`
+pub fn scan_card(
+    data: ScanData,
+    preferences: &Preferences,
+    warnings: &mut Vec<Error>,
+) -> Result<Card, Error> {
+    let name = if let Some(name) = data.name {
+        name
+    } else {
+        String::from("SD Card 1")
+    };
+
+    let steamapps_dir = data.card_path.join("steamapps");
+
+    let mut games = find_games(
+        &steamapps_dir.join("common"),
+        preferences.compute_sizes,
+        warnings,
+    )?;
+
+    let manifests = app_manifests(&steamapps_dir, warnings);
+    for game in &mut games {
+        if let Some(manifest) = manifests.get(&game.name) {
+            game.appid = Some(manifest.appid);
+            game.updated = manifest.last_updated.or(game.updated);
+            game.build = manifest.build;
+            game.state_flags = manifest.state_flags;
+        }
+    }
+
+    let (lutris, heroic) = find_other_game_folders(&data.card_path, preferences, warnings);
+
+    let card = Card {
+        // Collect all the data for the card before returning it from the function
+        uuid: data.uuid,
+        name,
+        games,
+        lutris,
+        heroic,
+        capacity: data.capacity,
+        free_space: free_space(&data.card_path).ok(),
+        mount_path: data.card_path,
+        archived: false,
+        hardware_id: data.hardware_id,
+        marker_id: data.marker_id,
+        hardware: data.hardware,
+        purchase_note: String::new(),
+        benchmarks: vec![],
+    };
+
+    if preferences.deep_scan != DeepScan::Off {
+        if let Err(e) = snapshot::record_scan(&card, preferences.deep_scan == DeepScan::Hashes) {
+            warnings.push(e);
+        }
+    }
+
+    Ok(card)
+}
+
+/// Runs the `lsblk` command on the system to get the UUID, size and Path for the inserted SD Cards
+pub fn discover_cards() -> Result<Vec<ScanData>, Error> {
+    let lsblk_error = |message: String| Error::Command {
+        program: "lsblk",
+        message,
+    };
+
+    // Run the lsblk command to get the currently inserted SD cards, -b prints the sizes in bytes
+    let output = Command::new("lsblk")
+        .arg("-b")
+        .arg("-o")
+        .arg("NAME,UUID,SIZE,MOUNTPOINT")
+        .output()
+        .map_err(|e| lsblk_error(e.to_string()))?;
+
+    // Turn the command output into a string
+    let s = str::from_utf8(&output.stdout)
+        .map_err(|e| lsblk_error(format!("its output isn't text: {e}")))?
+        .to_string();
+
+    let card_lines: Vec<String> = s // Find all lines that mention an SD card to be scanned
+        .lines()
+        .filter(|line| is_sd_card_line(line))
+        .map(|s| s.to_string())
+        .collect::<Vec<String>>();
+
+    let mut scan_data_list: Vec<ScanData> = vec![];
+
+    for card_line in card_lines {
+        let mut word_iter = card_line.split_whitespace();
+
+        // The partition's name, without the tree drawing lsblk puts in front of it
+        let partition = word_iter
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches(|c: char| !c.is_ascii_alphanumeric())
+            .to_string();
+
+        // Split the string by whitespace and get the second word, the UUID
+        let uuid: String = word_iter
+            .next()
+            .ok_or_else(|| lsblk_error(format!("no UUID for {partition}")))?
+            .to_string();
+
+        let capacity: Option<u64> = word_iter.next().and_then(|size| size.parse().ok());
+
+        // The mount point is the rest of the line, rejoin it in case the card's label has spaces in it
+        let card_path: PathBuf = match word_iter.collect::<Vec<&str>>().join(" ") {
+            path if path.is_empty() => continue,
+            path => PathBuf::from(path),
+        };
+
+        scan_data_list.push(ScanData {
+            uuid,
+            name: None,
+            capacity,
+            hardware_id: identity::read_hardware_id(&partition),
+            marker_id: identity::read_marker(&card_path),
+            hardware: CardHardware::read(&partition),
+            card_path,
+        })
+    }
+
+    Ok(scan_data_list)
+}
+
+/// Where the saved card with the UUID is mounted right now, None if it isn't inserted
+pub fn inserted_card_path(list: &[Card], card_uuid: &str) -> Option<PathBuf> {
+    discover_cards()
+        .ok()?
+        .into_iter()
+        .find(|scanned| {
+            identity::find_card(list, scanned).is_some_and(|index| list[index].uuid == card_uuid)
+        })
+        .map(|scanned| scanned.card_path)
+}
+
+/// Where the card list is saved, ~/.config/sdscannersave.json
+pub fn save_path() -> Result<PathBuf, Error> {
+    Ok(dirs::config_dir()
+        .ok_or(Error::NoConfigDir)?
+        .join("sdscannersave.json"))
+}
+
+/// Reads the saved card list, an empty list if nothing has been saved yet
+pub fn load_cards() -> Result<Vec<Card>, Error> {
+    let path = save_path()?;
+    if !path.is_file() {
+        // If there's no file at the save path it just hasn't been made yet
+        return Ok(vec![]);
+    }
+
+    let contents = fs::read_to_string(&path).map_err(Error::io("read", &path))?;
+    serde_json::from_str(&contents).map_err(Error::json(&path))
+}
+
+/// Formats a size in bytes into a short human readable string, e.g. `59.5 GB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Formats a unix timestamp as a UTC date, e.g. `2024-03-09`
+pub fn format_date(timestamp: u64) -> String {
+    // Howard Hinnant's days to civil date conversion
+    let days = (timestamp / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// The free space on the filesystem the path is on, in bytes
+pub fn free_space(path: &Path) -> Result<u64, Error> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let error = Error::io("check the free space of", path);
+    let c_path = match std::ffi::CString::new(path.as_os_str().as_bytes()) {
+        Ok(c_path) => c_path,
+        Err(e) => return Err(error(e.into())),
+    };
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: the path is a valid C string and stats is a valid statvfs to write into
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stats) } != 0 {
+        return Err(error(std::io::Error::last_os_error()));
+    }
+
+    Ok(stats.f_bavail as u64 * stats.f_frsize as u64)
+}
+
+/// Formats minutes played like Steam does, e.g. `45 min` or `12.5 hours`
+pub fn format_playtime(minutes: u64) -> String {
+    if minutes < 60 {
+        format!("{minutes} min")
+    } else {
+        format!("{:.1} hours", minutes as f64 / 60.0)
+    }
+}
+
+/// Scans the passed in folder for all the game's folders inside, fails if the folder itself can't be read.
+/// Folders that can't be read or don't have a UTF-8 name are skipped and added to `warnings`.
+/// The folder sizes are only added up if `compute_sizes` is set
+fn find_games(
+    search_dir: &Path,
+    compute_sizes: bool,
+    warnings: &mut Vec<Error>,
+) -> Result<Vec<Game>, Error> {
+    let mut list: Vec<Game> = sub_folders(search_dir, warnings)?
+        .into_iter()
+        .map(|(path, name)| Game {
+            // Get the folders name and size for the Game data, add that data to a list
+            name,
+            size: compute_sizes.then(|| dir_size(&path)),
+            updated: modified_time(&path),
+            ..Default::default()
+        })
+        .collect();
+
+    list.sort_by_key(|game| game.name.to_ascii_lowercase());
+
+    Ok(list)
+}
+
+/// The folders inside the passed in folder with their names. Entries that can't be read
+/// and folders with names that aren't UTF-8 are skipped and added to `warnings`
+fn sub_folders(dir: &Path, warnings: &mut Vec<Error>) -> Result<Vec<(PathBuf, String)>, Error> {
+    let mut folders = vec![];
+
+    for entry in fs::read_dir(dir).map_err(Error::io("read", dir))? {
+        let path = match entry {
+            Ok(entry) => entry.path(),
+            Err(e) => {
+                warnings.push(Error::io("read", dir)(e));
+                continue;
+            }
+        };
+        // Make sure the item in the folder is directory, removes things like dll files
+        if !path.is_dir() {
+            continue;
+        }
+
+        match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => {
+                let name = name.to_string();
+                folders.push((path, name));
+            }
+            None => warnings.push(Error::NotUtf8(path)),
+        }
+    }
+
+    Ok(folders)
+}
+
+/// Adds up the size of every file inside the passed in folder, files that can't be read are skipped
+pub fn dir_size(dir: &Path) -> u64 {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| Some((entry.path(), entry.metadata().ok()?)))
+        .map(|(path, metadata)| {
+            // DirEntry::metadata doesn't follow symlinks, so linked folders aren't counted twice
+            if metadata.is_dir() {
+                dir_size(&path)
+            } else {
+                metadata.len()
+            }
+        })
+        .sum()
+}
+
+/// Returns the path's modified time as a unix timestamp
+fn modified_time(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
+
+/// Reads the appmanifest_*.acf files in a steamapps folder and maps each game's install folder name to its manifest.
+/// Manifests that can't be read are left out, scanning the card lists them
+pub fn read_app_manifests(steamapps_dir: &Path) -> HashMap<String, AppManifest> {
+    app_manifests(steamapps_dir, &mut vec![])
+}
+
+fn app_manifests(steamapps_dir: &Path, warnings: &mut Vec<Error>) -> HashMap<String, AppManifest> {
+    let mut manifests = HashMap::new();
+
+    let entries = match fs::read_dir(steamapps_dir) {
+        Ok(entries) => entries,
+        Err(_) => return manifests,
+    };
+
+    for path in entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        let is_manifest = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("appmanifest_") && name.ends_with(".acf"));
+        if !is_manifest {
+            continue;
+        }
+
+        let manifest = fs::read_to_string(&path)
+            .map_err(Error::io("read", &path))
+            .and_then(|contents| AppManifest::parse(&contents).map_err(|e| e.in_file(&path)));
+        match manifest {
+            Ok(manifest) => {
+                manifests.insert(manifest.install_dir.clone(), manifest);
+            }
+            Err(e) => warnings.push(e),
+        }
+    }
+
+    manifests
+}
+
+/// How long the save queue waits for more changes before writing the save file
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Writes the card list to the save file on a background thread.
+/// Changes made close together are only written once, after they've settled for `SAVE_DELAY`
+pub struct SaveQueue {
+    sender: Option<mpsc::Sender<Vec<Card>>>,
+    thread: Option<JoinHandle<()>>,
+    /// Saves that failed since `take_errors` was last called
+    errors: Arc<Mutex<Vec<Error>>>,
+}
+
+impl SaveQueue {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel::<Vec<Card>>();
+        let errors = Arc::new(Mutex::new(vec![]));
+
+        let thread_errors = Arc::clone(&errors);
+        let thread = thread::spawn(move || {
+            let save = |list: &[Card]| {
+                if let Err(e) = save_cards(list) {
+                    if let Ok(mut errors) = thread_errors.lock() {
+                        errors.push(e);
+                    }
+                }
+            };
+
+            while let Ok(mut list) = receiver.recv() {
+                // Keep taking newer lists until the changes stop coming in, or the queue is flushed
+                loop {
+                    match receiver.recv_timeout(SAVE_DELAY) {
+                        Ok(newer) => list = newer,
+                        Err(RecvTimeoutError::Timeout) => break,
+                        Err(RecvTimeoutError::Disconnected) => {
+                            save(&list);
+                            return;
+                        }
+                    }
+                }
+                save(&list);
+            }
+        });
+
+        SaveQueue {
+            sender: Some(sender),
+            thread: Some(thread),
+            errors,
+        }
+    }
+
+    /// Queues the list to be saved
+    pub fn save(&self, list: &[Card]) {
+        let sent = self
+            .sender
+            .as_ref()
+            .is_some_and(|sender| sender.send(list.to_vec()).is_ok());
+
+        if !sent {
+            if let Err(e) = save_cards(list) {
+                self.push_error(e);
+            }
+        }
+    }
+
+    /// The saves that failed since the last call, the background thread has no one else to hand them to
+    pub fn take_errors(&self) -> Vec<Error> {
+        self.errors
+            .lock()
+            .map(|mut errors| std::mem::take(&mut *errors))
+            .unwrap_or_default()
+    }
+
+    fn push_error(&self, error: Error) {
+        if let Ok(mut errors) = self.errors.lock() {
+            errors.push(error);
+        }
+    }
+
+    /// Writes anything still waiting in the queue, used before the app exits
+    pub fn flush(&mut self) {
+        self.sender = None;
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                self.push_error(Error::Invalid(String::from(
+                    "The save thread crashed, the last changes might not be saved",
+                )));
+            }
+        }
+    }
+}
+
+/// Closing the window doesn't go through Exit, so whatever is still queued is written here
+impl Drop for SaveQueue {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl Default for SaveQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Saves the list of Card data into a json file into the user's .config folder. (~/.config/sdscannersave.json)
+pub fn save_cards(list: &[Card]) -> Result<(), Error> {
+    let path = save_path()?;
+    let s = serde_json::to_string(&list).map_err(Error::json(&path))?;
+    fs::write(&path, s).map_err(Error::io("write", &path))
+}
+
+/// Scan for Lutris and Heroic libraries and return them as a tuple (lutris, heroic).
+/// Libraries turned off in the preferences are skipped, and the preferences' extra search folders are tried when the card's root doesn't have one
+fn find_other_game_folders(
+    search_dir: &Path,
+    preferences: &Preferences,
+    warnings: &mut Vec<Error>,
+) -> (Option<OtherLibrary>, Option<OtherLibrary>) {
+    let mut found = vec![];
+    let mut search = |t: LibraryType| {
+        search_and_scan_folder(search_dir, t, preferences.compute_sizes, &mut found).or_else(|| {
+            preferences
+                .extra_search_roots
+                .iter()
+                .map(|root| search_dir.join(root))
+                .filter(|root| root.is_dir())
+                .find_map(|root| {
+                    search_and_scan_folder(&root, t, preferences.compute_sizes, &mut found)
+                })
+        })
+    };
+
+    let lutris: Option<OtherLibrary> = if preferences.scan_lutris {
+        search(LibraryType::Lutris)
+    } else {
+        None
+    };
+    let heroic: Option<OtherLibrary> = if preferences.scan_heroic {
+        search(LibraryType::Heroic)
+    } else {
+        None
+    };
+
+    // The same folders are looked through for each library, only report each problem once
+    for problem in found {
+        let message = problem.to_string();
+        if !warnings
+            .iter()
+            .any(|warning| warning.to_string() == message)
+        {
+            warnings.push(problem);
+        }
+    }
+
+    (lutris, heroic)
+}
+
+/// Scan for other game folders, uses LibraryType enum as a switch to check for different types of libraries (Lutris or Heroic)
+fn search_and_scan_folder(
+    card_path: &Path,
+    t: LibraryType,
+    compute_sizes: bool,
+    warnings: &mut Vec<Error>,
+) -> Option<OtherLibrary> {
+    let mut library = OtherLibrary::default();
+    // None when the folder couldn't be read, the error goes in the warnings
+    let mut scan_folder_for_library = |dir: &Path, t: LibraryType| {
+        scan_folder_for_library(dir, t, warnings)
+            .map_err(|e| warnings.push(e))
+            .ok()
+    };
+
+    match scan_folder_for_library(card_path, t) {
+        // Search for the library type in the card's root first,
+        Some(dirs) if dirs.len() == 1 => {
+            // If there's 1 found folder for the library type, assume it's the correct one
+            library.path = dirs[0].clone();
+        }
+
+        Some(dirs) if dirs.is_empty() => {
+            // If the searched for library wasn't found, search for the 'Other' library type
+            match scan_folder_for_library(card_path, LibraryType::Other) {
+                Some(dirs) if !dirs.is_empty() => {
+                    // Now, if we found an 'Other' folder, search it for the library type we're originally searching for
+                    for dir in dirs {
+                        match scan_folder_for_library(&dir, t) {
+                            Some(dirs) if dirs.len() == 1 => {
+                                library.path = dirs[0].clone();
+                                break;
+                            }
+                            Some(dirs) if dirs.is_empty() => continue,
+                            // In any case where lutris or heroic returns several folders, not sure what a good solution would be
+                            Some(_) => {}
+                            None => return None,
+                        }
+                    }
+                }
+                // Right now I'm only searching for folders that have the word "games" or "other" for a lutris or heroic folder, this happens if lutris and heroic weren't found at the SD card's root which I imagine should be the common configuration
+                Some(dirs) if dirs.is_empty() => {
+                    match scan_folder_for_library(card_path, LibraryType::Game) {
+                        Some(dirs) if !dirs.is_empty() => {
+                            for dir in dirs {
+                                match scan_folder_for_library(&dir, t) {
+                                    Some(dirs) if dirs.len() == 1 => {
+                                        library.path = dirs[0].clone();
+                                        break;
+                                    }
+                                    Some(dirs) if dirs.is_empty() => continue,
+                                    // Again, in any case where lutris or heroic returns several folders, not sure what a good solution would be
+                                    Some(_) => {}
+                                    None => return None,
+                                }
+                            }
+                        }
+                        Some(_) => {}
+                        None => return None,
+                    }
+                }
+                Some(_) => {}
+                // None(s) are only called when read_dir() encounters an error, we'll assume some sort of IO or file perm issue, doesn't matter
+                None => return None,
+            }
+        }
+        // Again, not sure how I'll handle situations where more than one folder is found
+        Some(_) => {}
+        None => return None,
+    };
+
+    // Check if there was any folder found for the library, if there wasn't exit the function
+    if library.path == PathBuf::new() {
+        return None;
+    }
+
+    library.games = match find_games(&library.path, compute_sizes, warnings) {
+        Ok(games) => games,
+        Err(e) => {
+            warnings.push(e);
+            return None;
+        }
+    };
+
+    Some(library)
+}
+
+/// Scans passed in dir based on passed in LibraryType returning the folders with the library's name in theirs, an empty Vec if none were found
+fn scan_folder_for_library(
+    dir: &Path,
+    t: LibraryType,
+    warnings: &mut Vec<Error>,
+) -> Result<Vec<PathBuf>, Error> {
+    let search = match t {
+        LibraryType::Lutris => "lutris",
+        LibraryType::Heroic => "heroic",
+        LibraryType::Other => "other",
+        LibraryType::Game => "game",
+    };
+
+    Ok(sub_folders(dir, warnings)?
+        .into_iter()
+        .filter(|(_, name)| name.to_ascii_lowercase().contains(search))
+        .map(|(path, _)| path)
+        .collect())
+}
+
+pub fn card_games_count(card: &Card) -> usize {
+    let mut count = card.games.len();
+
+    if let Some(heroic) = &card.heroic {
+        count += heroic.games.len();
+    }
+
+    if let Some(lutris) = &card.lutris {
+        count += lutris.games.len();
+    }
+
+    count
+}
+
+/// The default path to the SD card's root folder before v3.5 of SteamOS
+pub const OLD_SD_ROOT: &str = "/run/media/mmcblk0p1";
+/// The new mount folder for SD cards after v3.5 of SteamOS
+pub const NEW_SD_PATH: &str = "/run/media/deck";
+
+pub fn is_sd_card_line(line: &str) -> bool {
+    line.contains(OLD_SD_ROOT) | line.contains(NEW_SD_PATH) & line.contains("mmcblk")
+}
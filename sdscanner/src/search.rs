@@ -0,0 +1,424 @@
+use crate::scanning::{Card, Game, Library};
+use std::time::{SystemTime, UNIX_EPOCH};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+#[cfg(test)]
+mod tests;
+
+/// How a `size:` qualifier compares the game's size to the given value
+#[derive(Copy, Clone)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    /// Matches sizes that round down to the given value, `size:=20G` matches anything from 20G up to 21G
+    Equal(u64),
+}
+
+enum Filter {
+    /// `card:red`, the card's name has to contain the value
+    Card(String),
+    /// `lib:heroic`, only show games from that library
+    Library(Library),
+    /// `size:>20G`, compare the game's folder size
+    Size(Comparison, u64),
+    /// `appid:1245620`
+    AppId(u32),
+    /// `notplayed:6`, games not played in that many months, holds the unix timestamp they're compared to
+    NotPlayedSince(u64),
+}
+
+/// The average month, for `notplayed:`
+const SECONDS_PER_MONTH: u64 = 2_629_746;
+
+/// A single word of a name or search term after it's been normalized
+#[derive(Default)]
+struct Token {
+    text: String,
+    /// The char index in the original string of every char in `text`, used to highlight matches
+    positions: Vec<usize>,
+}
+
+/// A parsed search bar input, plain words are fuzzy matched against the game names
+/// and `field:value` qualifiers filter the list
+#[derive(Default)]
+pub struct Query {
+    terms: Vec<Token>,
+    filters: Vec<Filter>,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Query {
+        let mut query = Query::default();
+
+        for word in split_words(input) {
+            match parse_filter(&word) {
+                Some(filter) => query.filters.push(filter),
+                None => query.terms.extend(tokenize(&word)),
+            }
+        }
+
+        query
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty() && self.filters.is_empty()
+    }
+
+    /// True if there are any plain search words, as opposed to only qualifiers
+    pub fn has_terms(&self) -> bool {
+        !self.terms.is_empty()
+    }
+
+    /// Checks the card against any `card:` qualifiers
+    pub fn matches_card(&self, card: &Card) -> bool {
+        let name = normalize(&card.name);
+
+        self.filters.iter().all(|filter| match filter {
+            Filter::Card(value) => name.contains(value.as_str()),
+            _ => true,
+        })
+    }
+
+    /// Checks the library against any `lib:` qualifiers
+    pub fn matches_library(&self, library: Library) -> bool {
+        self.filters.iter().all(|filter| match filter {
+            Filter::Library(wanted) => *wanted == library,
+            _ => true,
+        })
+    }
+
+    /// Returns how well the game matches the query, higher is better. None if the game doesn't match
+    pub fn score(&self, game: &Game) -> Option<u32> {
+        for filter in &self.filters {
+            let matches = match filter {
+                Filter::Size(comparison, value) => game.size.is_some_and(|size| match comparison {
+                    Comparison::Less => size < *value,
+                    Comparison::LessOrEqual => size <= *value,
+                    Comparison::Greater => size > *value,
+                    Comparison::GreaterOrEqual => size >= *value,
+                    Comparison::Equal(unit) => {
+                        size >= *value && value.checked_add(*unit).is_none_or(|end| size < end)
+                    }
+                }),
+                Filter::AppId(appid) => game.appid == Some(*appid),
+                Filter::NotPlayedSince(cutoff) => game
+                    .last_played
+                    .is_none_or(|last_played| last_played < *cutoff),
+                _ => true,
+            };
+            if !matches {
+                return None;
+            }
+        }
+
+        let name = tokenize(&game.name);
+        self.terms
+            .iter()
+            .try_fold(0, |total, term| Some(total + match_term(term, &name)?.0))
+    }
+
+    /// Filters the games by the query and sorts them by their score, best matches first.
+    /// Games with the same score keep their original order
+    pub fn filter_games(&self, games: &[Game], library: Library) -> Vec<Game> {
+        if !self.matches_library(library) {
+            return vec![];
+        }
+
+        let mut scored: Vec<(u32, &Game)> = games
+            .iter()
+            .filter_map(|game| Some((self.score(game)?, game)))
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+        scored.into_iter().map(|(_, game)| game.clone()).collect()
+    }
+
+    /// Splits the name into (text, is_match) pieces so the matched parts can be drawn highlighted
+    pub fn highlight(&self, name: &str) -> Vec<(String, bool)> {
+        let tokens = tokenize(name);
+        let mut matched: Vec<usize> = self
+            .terms
+            .iter()
+            .filter_map(|term| match_term(term, &tokens))
+            .flat_map(|(_, positions)| positions)
+            .collect();
+        matched.sort_unstable();
+
+        let mut pieces: Vec<(String, bool)> = vec![];
+        for (index, c) in name.chars().enumerate() {
+            let is_match = matched.binary_search(&index).is_ok();
+            match pieces.last_mut() {
+                Some((text, last_match)) if *last_match == is_match => text.push(c),
+                _ => pieces.push((c.to_string(), is_match)),
+            }
+        }
+
+        pieces
+    }
+}
+
+/// Splits the input on whitespace, keeping anything in double quotes together, e.g. `card:"red one"`
+fn split_words(input: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let mut quoted = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words
+}
+
+/// Reads a `field:value` qualifier, returns None for plain words and unknown fields so they get searched as text
+fn parse_filter(word: &str) -> Option<Filter> {
+    let (field, value) = word.split_once(':')?;
+
+    match field.to_lowercase().as_str() {
+        "card" => Some(Filter::Card(normalize(value))),
+        "lib" | "library" => {
+            let value = normalize(value);
+            if value.is_empty() {
+                None
+            } else if "steam".starts_with(&value) {
+                Some(Filter::Library(Library::Steam))
+            } else if "lutris".starts_with(&value) {
+                Some(Filter::Library(Library::Lutris))
+            } else if "heroic".starts_with(&value) {
+                Some(Filter::Library(Library::Heroic))
+            } else {
+                None
+            }
+        }
+        "size" => {
+            let (comparison, value) = if let Some(value) = value.strip_prefix(">=") {
+                (Some(Comparison::GreaterOrEqual), value)
+            } else if let Some(value) = value.strip_prefix("<=") {
+                (Some(Comparison::LessOrEqual), value)
+            } else if let Some(value) = value.strip_prefix('>') {
+                (Some(Comparison::Greater), value)
+            } else if let Some(value) = value.strip_prefix('<') {
+                (Some(Comparison::Less), value)
+            } else {
+                (None, value.strip_prefix('=').unwrap_or(value))
+            };
+            let (size, unit) = parse_size(value)?;
+
+            Some(Filter::Size(
+                comparison.unwrap_or(Comparison::Equal(unit)),
+                size,
+            ))
+        }
+        "appid" => Some(Filter::AppId(value.parse().ok()?)),
+        "notplayed" => {
+            let months: u64 = value.trim_end_matches('m').parse().ok()?;
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs());
+            Some(Filter::NotPlayedSince(
+                now.saturating_sub(months.saturating_mul(SECONDS_PER_MONTH)),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Parses sizes like `20G`, `1.5GB` or `500m` into (bytes, bytes per unit), using the same 1000 based units as the size labels
+pub fn parse_size(value: &str) -> Option<(u64, u64)> {
+    let value = value.trim().to_ascii_uppercase();
+    let value = value.strip_suffix('B').unwrap_or(&value);
+
+    let (number, unit) = match value.chars().last()? {
+        'K' => (&value[..value.len() - 1], 1_000),
+        'M' => (&value[..value.len() - 1], 1_000_000),
+        'G' => (&value[..value.len() - 1], 1_000_000_000),
+        'T' => (&value[..value.len() - 1], 1_000_000_000_000),
+        _ => (value, 1),
+    };
+    let number: f64 = number
+        .trim()
+        .parse()
+        .ok()
+        .filter(|number: &f64| number.is_finite() && *number >= 0.0)?;
+
+    Some(((number * unit as f64) as u64, unit))
+}
+
+/// Folds a char for comparing, lowercases it and strips any accents, `É` becomes `e`
+fn fold_char(c: char) -> impl Iterator<Item = char> {
+    c.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+}
+
+/// Splits a string into normalized words. Punctuation separates words, apostrophes are dropped
+/// and a switch between letters and digits starts a new word, so "witcher3" becomes "witcher 3"
+fn tokenize(s: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut current = Token::default();
+
+    for (index, c) in s.chars().enumerate() {
+        if c == '\'' || c == '’' {
+            continue;
+        }
+
+        for folded in fold_char(c) {
+            if !folded.is_alphanumeric() {
+                if !current.text.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+                continue;
+            }
+
+            if let Some(last) = current.text.chars().last() {
+                if last.is_numeric() != folded.is_numeric() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            current.text.push(folded);
+            current.positions.push(index);
+        }
+    }
+    if !current.text.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
+/// The normalized words of a string joined together, used for the simple contains checks of the qualifiers
+fn normalize(s: &str) -> String {
+    tokenize(s).into_iter().map(|token| token.text).collect()
+}
+
+/// Turns roman numerals into digits so "ii" and "2" compare the same, other words are returned as is
+fn canonical(word: &str) -> String {
+    match roman_to_number(word) {
+        Some(number) => number.to_string(),
+        None => word.to_string(),
+    }
+}
+
+/// Reads roman numerals up to 39, which covers any sequel numbering. Returns None for anything else
+fn roman_to_number(word: &str) -> Option<u32> {
+    if word.is_empty() || word.len() > 7 {
+        return None;
+    }
+
+    let values: Vec<u32> = word
+        .chars()
+        .map(|c| match c {
+            'i' => Some(1),
+            'v' => Some(5),
+            'x' => Some(10),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+
+    let mut number = 0;
+    for (i, value) in values.iter().enumerate() {
+        match values.get(i + 1) {
+            Some(next) if next > value => number -= *value as i32,
+            _ => number += *value as i32,
+        }
+    }
+
+    // Only accept the standard way of writing the number, which rules out words like "ivx"
+    let number = u32::try_from(number).ok().filter(|number| *number > 0)?;
+    if number_to_roman(number) == word {
+        Some(number)
+    } else {
+        None
+    }
+}
+
+fn number_to_roman(mut number: u32) -> String {
+    let mut roman = String::new();
+    for (value, numeral) in [(10, "x"), (9, "ix"), (5, "v"), (4, "iv"), (1, "i")] {
+        while number >= value {
+            roman.push_str(numeral);
+            number -= value;
+        }
+    }
+    roman
+}
+
+/// Matches a single search term against the words of a name.
+/// Returns the score and the positions of the matched chars, None if the term doesn't match
+fn match_term(term: &Token, name: &[Token]) -> Option<(u32, Vec<usize>)> {
+    let term_canonical = canonical(&term.text);
+
+    // A whole word match, including roman numerals matching digits
+    if let Some(word) = name
+        .iter()
+        .find(|word| canonical(&word.text) == term_canonical)
+    {
+        return Some((100, word.positions.clone()));
+    }
+
+    // The start of a word
+    if let Some(word) = name.iter().find(|word| word.text.starts_with(&term.text)) {
+        let length = term.text.chars().count();
+        return Some((80, word.positions[..length].to_vec()));
+    }
+
+    // Somewhere inside a word
+    for word in name {
+        if let Some(byte_index) = word.text.find(&term.text) {
+            let start = word.text[..byte_index].chars().count();
+            let length = term.text.chars().count();
+            return Some((50, word.positions[start..start + length].to_vec()));
+        }
+    }
+
+    // Finally, the term's letters in order across the name, starting at the beginning of a word, "tw3" matches "The Witcher 3"
+    for start in 0..name.len() {
+        if let Some(positions) = match_subsequence(&term.text, &name[start..]) {
+            return Some((20, positions));
+        }
+    }
+
+    None
+}
+
+fn match_subsequence(term: &str, name: &[Token]) -> Option<Vec<usize>> {
+    let mut term_chars = term.chars().peekable();
+    let mut positions = vec![];
+
+    // The first char of the term has to be the first char of the first word
+    if name.first()?.text.chars().next()? != *term_chars.peek()? {
+        return None;
+    }
+
+    for word in name {
+        for (c, position) in word.text.chars().zip(&word.positions) {
+            match term_chars.peek() {
+                Some(wanted) if *wanted == c => {
+                    positions.push(*position);
+                    term_chars.next();
+                }
+                Some(_) => {}
+                None => return Some(positions),
+            }
+        }
+    }
+
+    if term_chars.peek().is_none() {
+        Some(positions)
+    } else {
+        None
+    }
+}
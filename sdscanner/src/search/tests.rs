@@ -0,0 +1,210 @@
+use super::*;
+
+fn game(name: &str, size: Option<u64>, last_played: Option<u64>) -> Game {
+    Game {
+        name: name.to_string(),
+        size,
+        appid: Some(1245620),
+        last_played,
+        ..Default::default()
+    }
+}
+
+fn words(s: &str) -> Vec<String> {
+    tokenize(s).into_iter().map(|token| token.text).collect()
+}
+
+fn score(query: &str, name: &str) -> Option<u32> {
+    Query::parse(query).score(&game(name, None, None))
+}
+
+#[test]
+fn tokenize_splits_and_folds_words() {
+    assert_eq!(
+        words("The Witcher 3: Wild Hunt"),
+        ["the", "witcher", "3", "wild", "hunt"]
+    );
+    assert_eq!(words("witcher3"), ["witcher", "3"]);
+    assert_eq!(words("Assassin's Creed"), ["assassins", "creed"]);
+    assert_eq!(words("Pokémon ÉCLAIR"), ["pokemon", "eclair"]);
+    assert_eq!(words("  --  "), Vec::<String>::new());
+    assert_eq!(words(""), Vec::<String>::new());
+}
+
+#[test]
+fn tokenize_keeps_the_original_positions() {
+    let tokens = tokenize("A-Éb");
+    assert_eq!(tokens[0].positions, [0]);
+    assert_eq!(tokens[1].text, "eb");
+    assert_eq!(tokens[1].positions, [2, 3]);
+}
+
+#[test]
+fn split_words_keeps_quotes_together() {
+    assert_eq!(
+        split_words(r#"card:"red one"  elden"#),
+        ["card:red one", "elden"]
+    );
+    assert_eq!(
+        split_words(r#"unclosed "quote here"#),
+        ["unclosed", "quote here"]
+    );
+}
+
+#[test]
+fn parses_sizes() {
+    assert_eq!(parse_size("20G"), Some((20_000_000_000, 1_000_000_000)));
+    assert_eq!(parse_size("1.5gb"), Some((1_500_000_000, 1_000_000_000)));
+    assert_eq!(parse_size(" 500m "), Some((500_000_000, 1_000_000)));
+    assert_eq!(
+        parse_size("2T"),
+        Some((2_000_000_000_000, 1_000_000_000_000))
+    );
+    assert_eq!(parse_size("123"), Some((123, 1)));
+}
+
+#[test]
+fn rejects_bad_sizes() {
+    assert_eq!(parse_size(""), None);
+    assert_eq!(parse_size("G"), None);
+    assert_eq!(parse_size("big"), None);
+    assert_eq!(parse_size("-5G"), None);
+    assert_eq!(parse_size("NaN"), None);
+    assert_eq!(parse_size("inf"), None);
+}
+
+#[test]
+fn huge_sizes_saturate() {
+    assert_eq!(parse_size("1e30G"), Some((u64::MAX, 1_000_000_000)));
+
+    let query = Query::parse("size:=1e30G");
+    assert_eq!(query.score(&game("Huge", Some(u64::MAX), None)), Some(0));
+    assert_eq!(query.score(&game("Small", Some(1), None)), None);
+}
+
+#[test]
+fn parses_filters() {
+    assert!(matches!(parse_filter("card:Red_One"), Some(Filter::Card(name)) if name == "redone"));
+    assert!(matches!(
+        parse_filter("lib:her"),
+        Some(Filter::Library(Library::Heroic))
+    ));
+    assert!(matches!(
+        parse_filter("LIBRARY:steam"),
+        Some(Filter::Library(Library::Steam))
+    ));
+    assert!(matches!(
+        parse_filter("size:>=20G"),
+        Some(Filter::Size(Comparison::GreaterOrEqual, 20_000_000_000))
+    ));
+    assert!(matches!(
+        parse_filter("size:<1g"),
+        Some(Filter::Size(Comparison::Less, 1_000_000_000))
+    ));
+    assert!(matches!(
+        parse_filter("size:=20G"),
+        Some(Filter::Size(
+            Comparison::Equal(1_000_000_000),
+            20_000_000_000
+        ))
+    ));
+    assert!(matches!(
+        parse_filter("appid:1245620"),
+        Some(Filter::AppId(1245620))
+    ));
+    assert!(matches!(
+        parse_filter("notplayed:6m"),
+        Some(Filter::NotPlayedSince(_))
+    ));
+}
+
+#[test]
+fn bad_filters_are_searched_as_text() {
+    for word in [
+        "elden",
+        "lib:",
+        "lib:epic",
+        "size:",
+        "size:>",
+        "size:huge",
+        "appid:",
+        "appid:-1",
+        "appid:99999999999",
+        "notplayed:",
+        "notplayed:-1",
+        "notplayed:soon",
+        "unknown:value",
+    ] {
+        assert!(parse_filter(word).is_none(), "{word} was read as a filter");
+    }
+
+    let query = Query::parse("lib:epic");
+    assert!(query.has_terms());
+}
+
+#[test]
+fn a_huge_notplayed_value_matches_everything_unplayed_since_the_epoch() {
+    assert!(matches!(
+        parse_filter("notplayed:99999999999999999"),
+        Some(Filter::NotPlayedSince(0))
+    ));
+    assert!(matches!(
+        parse_filter(&format!("notplayed:{}", u64::MAX)),
+        Some(Filter::NotPlayedSince(0))
+    ));
+
+    let query = Query::parse("notplayed:99999999999999999");
+    assert!(query.score(&game("Never played", None, None)).is_some());
+    assert!(query
+        .score(&game("Played", None, Some(1_700_000_000)))
+        .is_none());
+}
+
+#[test]
+fn reads_roman_numerals() {
+    assert_eq!(roman_to_number("ii"), Some(2));
+    assert_eq!(roman_to_number("iv"), Some(4));
+    assert_eq!(roman_to_number("xxxviii"), Some(38));
+    assert_eq!(roman_to_number("xxxix"), Some(39));
+    assert_eq!(roman_to_number("iiii"), None);
+    assert_eq!(roman_to_number("ivx"), None);
+    assert_eq!(roman_to_number("xl"), None);
+    assert_eq!(roman_to_number("xxxxxxxxxx"), None);
+    assert_eq!(roman_to_number("mix"), None);
+    assert_eq!(roman_to_number(""), None);
+}
+
+#[test]
+fn terms_match_from_best_to_worst() {
+    assert_eq!(score("witcher", "The Witcher 3"), Some(100));
+    assert_eq!(score("2", "Hades II"), Some(100));
+    assert_eq!(score("iii", "The Witcher 3"), Some(100));
+    assert_eq!(score("witch", "The Witcher 3"), Some(80));
+    assert_eq!(score("itch", "The Witcher 3"), Some(50));
+    assert_eq!(score("tw", "The Witcher 3"), Some(20));
+    assert_eq!(score("tw3", "The Witcher 3"), Some(120));
+    assert_eq!(score("elden", "The Witcher 3"), None);
+}
+
+#[test]
+fn every_term_has_to_match() {
+    assert_eq!(score("witcher 3", "The Witcher 3"), Some(200));
+    assert_eq!(score("witcher 4", "The Witcher 3"), None);
+    assert_eq!(score("", "The Witcher 3"), Some(0));
+}
+
+#[test]
+fn highlights_the_matched_chars() {
+    assert_eq!(
+        Query::parse("witch").highlight("The Witcher"),
+        [
+            (String::from("The "), false),
+            (String::from("Witch"), true),
+            (String::from("er"), false)
+        ]
+    );
+    assert_eq!(
+        Query::parse("pokemon").highlight("Pokémon"),
+        [(String::from("Pokémon"), true)]
+    );
+}
@@ -0,0 +1,451 @@
+use crate::error::Error;
+use crate::scanning::{Card, Library};
+use crate::steam;
+use crate::vdf::{self, ShortcutEntry, Value};
+use serde::Deserialize;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Files that ship next to a game's executable but aren't the game
+const NOT_THE_GAME: [&str; 10] = [
+    "unins",
+    "setup",
+    "install",
+    "redist",
+    "crash",
+    "dxweb",
+    "vc_",
+    "launcherhelper",
+    "uninstall",
+    "update",
+];
+
+/// How deep into a game's folder to look for its executable
+const EXECUTABLE_SEARCH_DEPTH: usize = 3;
+
+/// How a shortcut starts the game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Launch {
+    /// Through Lutris or Heroic, which keep the game's Wine settings
+    Launcher,
+    /// Runs the game's executable straight from its folder
+    Executable(PathBuf),
+}
+
+/// A Lutris or Heroic game on the card that can be added to Steam
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub library: Library,
+    pub game: String,
+    /// The game's folder on the card
+    pub path: PathBuf,
+    /// The biggest executable found in the game's folder
+    pub executable: Option<PathBuf>,
+    pub launch: Launch,
+    /// Steam already has a shortcut with the game's name
+    pub in_steam: bool,
+}
+
+impl Candidate {
+    /// Windows games started straight from their executable need Proton picked in Steam
+    pub fn proton_warning(&self) -> Option<String> {
+        match &self.launch {
+            Launch::Executable(path) if is_windows_executable(path) => Some(format!(
+                "Pick Proton under {}'s Properties, Compatibility in Steam",
+                self.game
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// One entry in Steam's shortcuts.vdf
+#[derive(Debug, Clone)]
+pub struct Shortcut {
+    pub app_name: String,
+    /// The program Steam runs, quoted like Steam writes it
+    pub exe: String,
+    pub start_dir: String,
+    pub launch_options: String,
+}
+
+/// What adding shortcuts did
+#[derive(Debug, Clone)]
+pub struct Added {
+    pub added: Vec<String>,
+    /// Games that already had a shortcut
+    pub skipped: Vec<String>,
+    /// Where the shortcuts.vdf from before was copied to, None if Steam had no shortcuts yet
+    pub backup: Option<PathBuf>,
+}
+
+impl Added {
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "Added {} games to Steam, start Steam (or go back to Game Mode) to see them",
+            self.added.len()
+        );
+        if !self.skipped.is_empty() {
+            summary.push_str(&format!(". Already in Steam: {}", self.skipped.join(", ")));
+        }
+        if let Some(backup) = &self.backup {
+            summary.push_str(&format!(
+                ". The old shortcuts were saved to {}",
+                backup.display()
+            ));
+        }
+        summary
+    }
+}
+
+fn shortcuts_path() -> Result<PathBuf, Error> {
+    steam::user_config_dir()
+        .map(|dir| dir.join("shortcuts.vdf"))
+        .ok_or(Error::NoSteamUser)
+}
+
+/// Reads the Steam user's shortcuts.vdf, empty if they don't have any shortcuts yet
+fn read_shortcuts_file(path: &Path) -> Result<Vec<u8>, Error> {
+    match fs::read(path) {
+        Ok(contents) => Ok(contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(Error::io("read", path)(e)),
+    }
+}
+
+/// Reads the Steam user's shortcuts, an empty list if they don't have any yet
+fn read_shortcuts(path: &Path) -> Result<Value, Error> {
+    vdf::parse_binary(&read_shortcuts_file(path)?).map_err(|e| e.in_file(path))
+}
+
+/// Whether Steam already has a shortcut with the name, or one that starts the same thing
+fn is_duplicate(existing: &[ShortcutEntry], shortcut: &Shortcut) -> bool {
+    existing.iter().any(|entry| {
+        entry.app_name.eq_ignore_ascii_case(&shortcut.app_name)
+            || (entry.exe == shortcut.exe && entry.launch_options == shortcut.launch_options)
+    })
+}
+
+/// The card's Lutris and Heroic games with how they'd be started from Steam.
+/// The card has to be inserted and mounted at its `mount_path`
+pub fn candidates(card: &Card) -> Result<Vec<Candidate>, Error> {
+    let existing = ShortcutEntry::list(&read_shortcuts(&shortcuts_path()?)?);
+    let mut candidates = vec![];
+
+    for library in [Library::Lutris, Library::Heroic] {
+        for game in card.library_games(library) {
+            let path = match card.game_path(library, game) {
+                Some(path) if path.is_dir() => path,
+                _ => continue,
+            };
+
+            candidates.push(Candidate {
+                library,
+                game: game.name.clone(),
+                executable: find_executable(&path),
+                path,
+                launch: Launch::Launcher,
+                in_steam: existing
+                    .iter()
+                    .any(|entry| entry.app_name.eq_ignore_ascii_case(&game.name)),
+            });
+        }
+    }
+
+    Ok(candidates)
+}
+
+/// The game's biggest executable that doesn't look like an installer or crash reporter
+fn find_executable(dir: &Path) -> Option<PathBuf> {
+    let mut found = vec![];
+    collect_executables(dir, 0, &mut found);
+
+    found
+        .into_iter()
+        .max_by_key(|(size, _)| *size)
+        .map(|(_, path)| path)
+}
+
+fn collect_executables(dir: &Path, depth: usize, found: &mut Vec<(u64, PathBuf)>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+
+        if metadata.is_dir() {
+            if depth < EXECUTABLE_SEARCH_DEPTH {
+                collect_executables(&path, depth + 1, found);
+            }
+            continue;
+        }
+
+        let name = entry.file_name().to_string_lossy().to_lowercase();
+        if NOT_THE_GAME.iter().any(|skip| name.contains(skip)) {
+            continue;
+        }
+
+        let runnable = is_windows_executable(&path)
+            || name.ends_with(".sh")
+            || (metadata.permissions().mode() & 0o111 != 0 && is_elf(&path));
+        if runnable {
+            found.push((metadata.len(), path));
+        }
+    }
+}
+
+fn is_windows_executable(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("exe"))
+}
+
+fn is_elf(path: &Path) -> bool {
+    use std::io::Read;
+
+    let mut magic = [0; 4];
+    fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|()| magic == *b"\x7fELF")
+}
+
+/// The command that runs a Flatpak app if it's installed for the user or the system, otherwise the native program
+fn launcher_command(flatpak_id: &str, program: &str) -> (String, String) {
+    let installed = ["/var/lib/flatpak/app"]
+        .into_iter()
+        .map(PathBuf::from)
+        .chain(dirs::data_dir().map(|dir| dir.join("flatpak/app")))
+        .any(|dir| dir.join(flatpak_id).is_dir());
+
+    if installed {
+        (String::from("flatpak"), format!("run {flatpak_id} "))
+    } else {
+        (program.to_string(), String::new())
+    }
+}
+
+/// Lutris names a game's folder after its slug, the lowercased name with dashes
+fn lutris_slug(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// An installed game in one of Heroic's installed.json files
+#[derive(Deserialize)]
+struct HeroicInstall {
+    #[serde(alias = "appName")]
+    app_name: String,
+    install_path: String,
+}
+
+/// Heroic launches games by their store app name, looked up from where Heroic installed them.
+/// Falls back to the folder name
+fn heroic_app_name(game_path: &Path) -> Option<String> {
+    let config = dirs::home_dir()?.join(".var/app/com.heroicgameslauncher.hgl/config");
+    let files = [
+        config.join("legendary/installed.json"),
+        config.join("heroic/legendaryConfig/legendary/installed.json"),
+        config.join("heroic/gog_store/installed.json"),
+    ];
+
+    files
+        .iter()
+        .filter_map(|file| fs::read_to_string(file).ok())
+        .flat_map(|contents| {
+            // Legendary keys the installs by app name, GOG keeps them in a list
+            let value: serde_json::Value = serde_json::from_str(&contents).unwrap_or_default();
+            let installs = match value.get("installed") {
+                Some(installed) => installed.clone(),
+                None => serde_json::Value::Array(
+                    value
+                        .as_object()
+                        .map_or(vec![], |installs| installs.values().cloned().collect()),
+                ),
+            };
+            serde_json::from_value::<Vec<HeroicInstall>>(installs).unwrap_or_default()
+        })
+        .find(|install| Path::new(&install.install_path).file_name() == game_path.file_name())
+        .map(|install| install.app_name)
+}
+
+fn quoted(path: &Path) -> String {
+    format!("\"{}\"", path.display())
+}
+
+/// The shortcut that starts the candidate the way it's set to launch
+pub fn shortcut(candidate: &Candidate) -> Shortcut {
+    match &candidate.launch {
+        Launch::Executable(executable) => Shortcut {
+            app_name: candidate.game.clone(),
+            exe: quoted(executable),
+            start_dir: quoted(executable.parent().unwrap_or(&candidate.path)),
+            launch_options: String::new(),
+        },
+        Launch::Launcher => {
+            let (program, options) = match candidate.library {
+                Library::Heroic => {
+                    let (program, prefix) =
+                        launcher_command("com.heroicgameslauncher.hgl", "heroic");
+                    let app_name =
+                        heroic_app_name(&candidate.path).unwrap_or_else(|| candidate.game.clone());
+                    (
+                        program,
+                        format!("{prefix}--no-gui --no-sandbox \"heroic://launch/{app_name}\""),
+                    )
+                }
+                _ => {
+                    let (program, prefix) = launcher_command("net.lutris.Lutris", "lutris");
+                    (
+                        program,
+                        format!("{prefix}lutris:rungame/{}", lutris_slug(&candidate.game)),
+                    )
+                }
+            };
+
+            Shortcut {
+                app_name: candidate.game.clone(),
+                exe: format!("\"{program}\""),
+                start_dir: quoted(&candidate.path),
+                launch_options: options,
+            }
+        }
+    }
+}
+
+/// The ID Steam gives a shortcut, used for its artwork and controller config
+fn shortcut_appid(shortcut: &Shortcut) -> u32 {
+    crc32(format!("{}{}", shortcut.exe, shortcut.app_name).as_bytes()) | 0x8000_0000
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn shortcut_entry(shortcut: &Shortcut) -> Value {
+    let string = |key: &str, value: &str| (key.to_string(), Value::String(value.to_string()));
+    let int = |key: &str, value: u32| (key.to_string(), Value::Int(value));
+
+    Value::Table(vec![
+        int("appid", shortcut_appid(shortcut)),
+        string("AppName", &shortcut.app_name),
+        string("Exe", &shortcut.exe),
+        string("StartDir", &shortcut.start_dir),
+        string("icon", ""),
+        string("ShortcutPath", ""),
+        string("LaunchOptions", &shortcut.launch_options),
+        int("IsHidden", 0),
+        int("AllowDesktopConfig", 1),
+        int("AllowOverlay", 1),
+        int("OpenVR", 0),
+        int("Devkit", 0),
+        string("DevkitGameID", ""),
+        int("DevkitOverrideAppID", 0),
+        int("LastPlayTime", 0),
+        string("FlatpakAppID", ""),
+        (String::from("tags"), Value::Table(vec![])),
+    ])
+}
+
+/// Adds the shortcuts to the Steam user's shortcuts.vdf, skipping any game that already has one.
+/// The file is backed up first, Steam only picks the new shortcuts up after it restarts
+pub fn add(shortcuts: &[Shortcut]) -> Result<Added, Error> {
+    // Steam writes the shortcuts it has loaded back when it exits, which would drop the new ones
+    if steam::running() {
+        return Err(Error::SteamRunning {
+            doing: "adding games to it, it would overwrite the new shortcuts when it exits",
+        });
+    }
+
+    let path = shortcuts_path()?;
+    let contents = read_shortcuts_file(&path)?;
+    let file = vdf::parse_binary(&contents).map_err(|e| e.in_file(&path))?;
+    let mut existing = ShortcutEntry::list(&file);
+
+    let mut added = vec![];
+    let mut skipped = vec![];
+    let mut new_entries = vec![];
+    for shortcut in shortcuts {
+        if is_duplicate(&existing, shortcut) {
+            skipped.push(shortcut.app_name.clone());
+            continue;
+        }
+        existing.push(ShortcutEntry {
+            appid: None,
+            app_name: shortcut.app_name.clone(),
+            exe: shortcut.exe.clone(),
+            start_dir: shortcut.start_dir.clone(),
+            launch_options: shortcut.launch_options.clone(),
+        });
+        added.push(shortcut.app_name.clone());
+        new_entries.push(shortcut_entry(shortcut));
+    }
+
+    if new_entries.is_empty() {
+        return Ok(Added {
+            added,
+            skipped,
+            backup: None,
+        });
+    }
+
+    let backup = if path.exists() {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        let backup = path.with_file_name(format!("shortcuts.vdf.{timestamp}.bak"));
+        // Nothing is changed if the backup fails
+        fs::copy(&path, &backup).map_err(Error::io("back up", &path))?;
+        Some(backup)
+    } else {
+        None
+    };
+
+    // Shortcuts are keyed by their position in the list
+    let first = file
+        .get("shortcuts")
+        .map_or(&[][..], Value::entries)
+        .iter()
+        .filter_map(|(key, _)| key.parse::<usize>().ok())
+        .max()
+        .map_or(0, |last| last + 1);
+    let new_entries: Vec<(String, Value)> = (first..)
+        .zip(new_entries)
+        .map(|(key, entry)| (key.to_string(), entry))
+        .collect();
+    // The existing shortcuts are kept byte for byte, their names and paths don't have to be UTF-8
+    let contents =
+        vdf::append_binary(&contents, "shortcuts", &new_entries).map_err(|e| e.in_file(&path))?;
+
+    // Written next to it and renamed over it so Steam never sees half a file
+    let partial = path.with_file_name("shortcuts.vdf.sdscanner-part");
+    fs::write(&partial, contents)
+        .and_then(|()| fs::rename(&partial, &path))
+        .map_err(Error::io("write", &path))?;
+
+    Ok(Added {
+        added,
+        skipped,
+        backup,
+    })
+}
@@ -0,0 +1,270 @@
+use crate::error::Error;
+use crate::scanning::{Card, Library};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+/// One file in a game's folder
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FileEntry {
+    /// The file's path inside the game's folder
+    pub path: String,
+    pub size: u64,
+    /// When the file was last modified, as a unix timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtime: Option<u64>,
+    /// A hash of the file's contents, only made by deep scans with hashing on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+/// The files a game's folder had the last time it verified clean
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct GameSnapshot {
+    /// Steam's build ID when the snapshot was taken, an update makes the snapshot outdated
+    #[serde(default)]
+    pub build: Option<u32>,
+    pub files: Vec<FileEntry>,
+}
+
+/// Every game's snapshot on one card, keyed by `snapshot_key`
+pub type CardSnapshots = HashMap<String, GameSnapshot>;
+
+/// What the last deep scan found in a game's folder
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct GameListing {
+    /// When the listing was made, as a unix timestamp
+    pub scanned: u64,
+    pub files: Vec<FileEntry>,
+    /// The latest changes a deep scan found, kept until a later scan finds different ones
+    #[serde(default)]
+    pub changes: Option<FileChanges>,
+}
+
+/// The files that changed in a game's folder between two deep scans, paths inside the game's folder
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct FileChanges {
+    /// When the scan that found the changes ran, as a unix timestamp
+    pub found: u64,
+    /// When the scan the changes are compared to ran
+    pub since: u64,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+}
+
+impl FileChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Every game's deep scan listing on one card, keyed by `snapshot_key`
+pub type CardListings = HashMap<String, GameListing>;
+
+/// The snapshots and listings are kept out of the main save file since they can get big,
+/// two files per card in ~/.config/sdscanner-snapshots
+fn store_path(file_name: &str) -> Result<PathBuf, Error> {
+    Ok(dirs::config_dir()
+        .ok_or(Error::NoConfigDir)?
+        .join("sdscanner-snapshots")
+        .join(file_name))
+}
+
+pub fn snapshot_key(library: Library, game_name: &str) -> String {
+    format!("{library:?}/{game_name}")
+}
+
+/// Reads the card's verify snapshots, empty if there aren't any yet
+pub fn load(card_uuid: &str) -> Result<CardSnapshots, Error> {
+    load_store(&format!("{card_uuid}.json"))
+}
+
+pub fn save(card_uuid: &str, snapshots: &CardSnapshots) -> Result<(), Error> {
+    save_store(&format!("{card_uuid}.json"), snapshots)
+}
+
+/// Reads the card's deep scan listings, empty if it was never deep scanned
+pub fn load_listings(card_uuid: &str) -> Result<CardListings, Error> {
+    load_store(&format!("{card_uuid}-scan.json"))
+}
+
+pub fn save_listings(card_uuid: &str, listings: &CardListings) -> Result<(), Error> {
+    save_store(&format!("{card_uuid}-scan.json"), listings)
+}
+
+fn load_store<T: DeserializeOwned + Default>(file_name: &str) -> Result<T, Error> {
+    let path = store_path(file_name)?;
+    if !path.is_file() {
+        return Ok(T::default());
+    }
+
+    let contents = fs::read_to_string(&path).map_err(Error::io("read", &path))?;
+    serde_json::from_str(&contents).map_err(Error::json(&path))
+}
+
+fn save_store<T: Serialize>(file_name: &str, store: &T) -> Result<(), Error> {
+    let path = store_path(file_name)?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(Error::io("create", dir))?;
+    }
+
+    let s = serde_json::to_string(store).map_err(Error::json(&path))?;
+    fs::write(&path, s).map_err(Error::io("save", &path))
+}
+
+/// Lists every game folder on the card and compares it to the listing from the last deep scan,
+/// saving the new listings and any changes found. Hashes every file if `hash_files` is set, which reads the whole card
+pub fn record_scan(card: &Card, hash_files: bool) -> Result<(), Error> {
+    let mut listings = load_listings(&card.uuid)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+
+    for library in [Library::Steam, Library::Lutris, Library::Heroic] {
+        for game in card.library_games(library) {
+            let game_dir = match card.game_path(library, game) {
+                Some(game_dir) if game_dir.is_dir() => game_dir,
+                _ => continue,
+            };
+            let key = snapshot_key(library, &game.name);
+            let files = list_files(&game_dir, hash_files);
+
+            let changes = match listings.remove(&key) {
+                Some(old) => {
+                    let changes = compare(&old.files, &files, old.scanned, now);
+                    if changes.is_empty() {
+                        old.changes
+                    } else {
+                        Some(changes)
+                    }
+                }
+                None => None,
+            };
+
+            listings.insert(
+                key,
+                GameListing {
+                    scanned: now,
+                    files,
+                    changes,
+                },
+            );
+        }
+    }
+
+    save_listings(&card.uuid, &listings)
+}
+
+/// The changes a deep scan found in a game's folder, None if it hasn't found any
+pub fn game_changes(
+    card_uuid: &str,
+    library: Library,
+    game_name: &str,
+) -> Result<Option<FileChanges>, Error> {
+    Ok(load_listings(card_uuid)?
+        .remove(&snapshot_key(library, game_name))
+        .and_then(|listing| listing.changes))
+}
+
+/// A file counts as modified if its size changed, or its hash if both listings have one, otherwise its modified time
+fn compare(old: &[FileEntry], new: &[FileEntry], since: u64, found: u64) -> FileChanges {
+    let old_files: HashMap<&str, &FileEntry> =
+        old.iter().map(|file| (file.path.as_str(), file)).collect();
+    let new_files: HashMap<&str, &FileEntry> =
+        new.iter().map(|file| (file.path.as_str(), file)).collect();
+
+    let mut changes = FileChanges {
+        found,
+        since,
+        ..Default::default()
+    };
+
+    for file in new {
+        match old_files.get(file.path.as_str()) {
+            None => changes.added.push(file.path.clone()),
+            Some(old) => {
+                let modified = old.size != file.size
+                    || match (&old.hash, &file.hash) {
+                        (Some(old_hash), Some(new_hash)) => old_hash != new_hash,
+                        _ => old.mtime != file.mtime,
+                    };
+                if modified {
+                    changes.modified.push(file.path.clone());
+                }
+            }
+        }
+    }
+    changes.removed = old
+        .iter()
+        .filter(|file| !new_files.contains_key(file.path.as_str()))
+        .map(|file| file.path.clone())
+        .collect();
+
+    changes
+}
+
+/// Lists every file in a game's folder, sorted by path. Hashes their contents if `hash_files` is set
+pub fn list_files(game_dir: &Path, hash_files: bool) -> Vec<FileEntry> {
+    let mut files = vec![];
+    add_files(game_dir, game_dir, hash_files, &mut files);
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    files
+}
+
+fn add_files(root: &Path, dir: &Path, hash_files: bool, files: &mut Vec<FileEntry>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+        let path = entry.path();
+
+        if metadata.is_dir() {
+            add_files(root, &path, hash_files, files);
+        } else if metadata.is_file() {
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            files.push(FileEntry {
+                path: relative.to_string_lossy().into_owned(),
+                size: metadata.len(),
+                mtime: metadata
+                    .modified()
+                    .ok()
+                    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map(|time| time.as_secs()),
+                hash: if hash_files { hash_file(&path) } else { None },
+            });
+        }
+    }
+}
+
+/// A 64 bit FNV-1a hash of the file, only used to spot changed files so it doesn't need to be a strong one
+pub fn hash_file(path: &Path) -> Option<String> {
+    let mut file = File::open(path).ok()?;
+    let mut buffer = vec![0; 1024 * 1024];
+    let mut hash: u64 = 0xcbf29ce484222325;
+
+    loop {
+        let read = file.read(&mut buffer).ok()?;
+        if read == 0 {
+            break;
+        }
+        for byte in &buffer[..read] {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    Some(format!("{hash:016x}"))
+}
@@ -0,0 +1,247 @@
+use crate::error::Error;
+use crate::scanning::{self, Card, NEW_SD_PATH, OLD_SD_ROOT};
+use crate::vdf::{self, AppManifest, LibraryFolder, LocalApp, LocalConfig};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+/// Steam's own folders on the internal drive that show up like games
+const TOOL_FOLDERS: [&str; 3] = ["Proton", "SteamLinuxRuntime", "Steamworks Shared"];
+
+/// Steam's folder on the Deck's internal drive, it holds the internal library and the user data
+pub fn root() -> Option<PathBuf> {
+    Some(dirs::home_dir()?.join(".local/share/Steam"))
+}
+
+/// Moving files around under a running Steam can corrupt its library, so transfers only run while it's closed
+pub fn running() -> bool {
+    let entries = match fs::read_dir("/proc") {
+        Ok(entries) => entries,
+        Err(_) => return false,
+    };
+
+    entries.filter_map(|entry| entry.ok()).any(|entry| {
+        fs::read_to_string(entry.path().join("comm")).is_ok_and(|comm| comm.trim() == "steam")
+    })
+}
+
+/// The config folder of the Steam user that logged in last, the one whose localconfig.vdf changed most recently
+pub fn user_config_dir() -> Option<PathBuf> {
+    fs::read_dir(root()?.join("userdata"))
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join("config"))
+        .filter_map(|dir| {
+            let modified = fs::metadata(dir.join("localconfig.vdf"))
+                .ok()?
+                .modified()
+                .ok()?;
+            Some((modified, dir))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, dir)| dir)
+}
+
+fn localconfig_path() -> Option<PathBuf> {
+    Some(user_config_dir()?.join("localconfig.vdf"))
+}
+
+/// Reads the apps section of the Steam user's localconfig.vdf, keyed by app ID
+pub fn local_apps() -> Result<HashMap<u32, LocalApp>, Error> {
+    let path = localconfig_path().ok_or(Error::NoSteamUser)?;
+    let contents = fs::read_to_string(&path).map_err(Error::io("read", &path))?;
+    let config = LocalConfig::parse(&contents).map_err(|e| e.in_file(&path))?;
+
+    Ok(config.apps)
+}
+
+/// Copies the last played time and playtime from Steam's user config to every Steam game on the cards, matched by app ID.
+/// Games Steam has no record of keep what they had, and all of them do if Steam's user config can't be read
+pub fn attach_play_data(list: &mut [Card]) -> Result<(), Error> {
+    let local = local_apps()?;
+
+    for game in list.iter_mut().flat_map(|card| &mut card.games) {
+        if let Some(app) = game.appid.and_then(|appid| local.get(&appid)) {
+            game.last_played = app.last_played.or(game.last_played);
+            game.playtime = app.playtime.or(game.playtime);
+        }
+    }
+
+    Ok(())
+}
+
+/// Where a copy of a game is installed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+    /// The Deck's internal drive
+    Internal,
+    Card {
+        card_uuid: String,
+        card_name: String,
+        inserted: bool,
+    },
+}
+
+/// A game Steam on this Deck knows about, with everywhere it's installed
+#[derive(Debug, Clone)]
+pub struct LibraryEntry {
+    pub name: String,
+    pub last_played: Option<u64>,
+    /// Empty if it isn't installed anywhere
+    pub locations: Vec<Location>,
+}
+
+impl LibraryEntry {
+    /// Installed only on cards that aren't inserted, so Steam shows it as uninstalled
+    pub fn only_on_other_cards(&self) -> bool {
+        !self.locations.is_empty()
+            && self.locations.iter().all(|location| {
+                matches!(
+                    location,
+                    Location::Card {
+                        inserted: false,
+                        ..
+                    }
+                )
+            })
+    }
+}
+
+/// Every game installed on the internal drive or played on this Deck, with where its copies are,
+/// most recently played first. `inserted` has the UUIDs of the saved cards that are inserted right now.
+/// Steam's files that only fill in extra games or names are added to `warnings` if they can't be read
+pub fn library(
+    list: &[Card],
+    inserted: &HashSet<String>,
+    warnings: &mut Vec<Error>,
+) -> Result<Vec<LibraryEntry>, Error> {
+    let local = local_apps()?;
+
+    let mut names: HashMap<u32, String> = HashMap::new();
+    let mut locations: HashMap<u32, Vec<Location>> = HashMap::new();
+
+    for manifest in internal_manifests(warnings) {
+        if TOOL_FOLDERS
+            .iter()
+            .any(|tool| manifest.install_dir.starts_with(tool))
+        {
+            continue;
+        }
+        names.insert(
+            manifest.appid,
+            manifest.name.unwrap_or(manifest.install_dir),
+        );
+        let app_locations = locations.entry(manifest.appid).or_default();
+        if !app_locations.contains(&Location::Internal) {
+            app_locations.push(Location::Internal);
+        }
+    }
+
+    for card in list {
+        for game in &card.games {
+            let appid = match game.appid {
+                Some(appid) => appid,
+                None => continue,
+            };
+            names.entry(appid).or_insert_with(|| game.name.clone());
+            locations.entry(appid).or_default().push(Location::Card {
+                card_uuid: card.uuid.clone(),
+                card_name: card.name.clone(),
+                inserted: inserted.contains(&card.uuid),
+            });
+        }
+    }
+
+    // Games on the cards that were never played on this Deck aren't part of its library
+    let appids: HashSet<u32> = local
+        .iter()
+        .filter(|(_, app)| app.last_played.is_some())
+        .map(|(appid, _)| *appid)
+        .chain(
+            locations
+                .iter()
+                .filter(|(_, locations)| locations.contains(&Location::Internal))
+                .map(|(appid, _)| *appid),
+        )
+        .collect();
+
+    // Games that aren't installed anywhere get their name from Steam's store info cache
+    let unnamed: HashSet<u32> = appids
+        .iter()
+        .filter(|appid| !names.contains_key(appid))
+        .copied()
+        .collect();
+    if !unnamed.is_empty() {
+        match appinfo_names(&unnamed) {
+            Ok(appinfo_names) => names.extend(appinfo_names),
+            Err(e) => warnings.push(e),
+        }
+    }
+
+    let mut entries: Vec<LibraryEntry> = appids
+        .into_iter()
+        .filter(|appid| names.contains_key(appid) || local.contains_key(appid))
+        .map(|appid| LibraryEntry {
+            name: names
+                .get(&appid)
+                .cloned()
+                .unwrap_or_else(|| format!("App {appid}")),
+            last_played: local.get(&appid).and_then(|app| app.last_played),
+            locations: locations.remove(&appid).unwrap_or_default(),
+        })
+        .collect();
+
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.last_played));
+    Ok(entries)
+}
+
+/// The manifests of the games in every Steam library on the Deck's internal drive,
+/// libraries on the SD card are left out since the cards are scanned separately.
+/// If the library list can't be read only the default library is used
+fn internal_manifests(warnings: &mut Vec<Error>) -> Vec<AppManifest> {
+    let root = match root() {
+        Some(root) => root,
+        None => return vec![],
+    };
+
+    let path = root.join("steamapps/libraryfolders.vdf");
+    let folders = fs::read_to_string(&path)
+        .map_err(Error::io("read", &path))
+        .and_then(|contents| LibraryFolder::parse_all(&contents).map_err(|e| e.in_file(&path)));
+    let mut library_paths: Vec<PathBuf> = match folders {
+        Ok(folders) => folders
+            .into_iter()
+            .map(|folder| folder.path)
+            .filter(|path| !path.starts_with(OLD_SD_ROOT) && !path.starts_with(NEW_SD_PATH))
+            .collect(),
+        Err(e) => {
+            warnings.push(e);
+            vec![]
+        }
+    };
+    if !library_paths.contains(&root) {
+        library_paths.push(root);
+    }
+
+    library_paths
+        .iter()
+        .flat_map(|path| scanning::read_app_manifests(&path.join("steamapps")).into_values())
+        .collect()
+}
+
+/// The names of the apps from Steam's appinfo.vdf, the apps it doesn't have are left out
+fn appinfo_names(appids: &HashSet<u32>) -> Result<HashMap<u32, String>, Error> {
+    let path = match root() {
+        Some(root) => root.join("appcache/appinfo.vdf"),
+        None => return Ok(HashMap::new()),
+    };
+    let contents = fs::read(&path).map_err(Error::io("read", &path))?;
+    let appinfo = vdf::read_appinfo(&contents).map_err(|e| e.in_file(&path))?;
+
+    Ok(appinfo
+        .apps
+        .iter()
+        .filter(|app| appids.contains(&app.appid))
+        .filter_map(|app| Some((app.appid, appinfo.name(app)?)))
+        .collect())
+}
@@ -0,0 +1,412 @@
+use crate::error::Error;
+use crate::scanning::{self, Card, Game};
+use crate::snapshot;
+use crate::steam;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Added to a file's name while it's being copied, a leftover one is picked up where it stopped
+const PART_SUFFIX: &str = ".sdscanner-part";
+const BUFFER_SIZE: usize = 1024 * 1024;
+/// How often the progress callback is called while copying or verifying
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A Steam game to copy or move from one library to another
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Transfer {
+    /// The game's folder name in steamapps/common
+    pub game: String,
+    pub appid: u32,
+    /// The root of the library the game is on, a card's mount path or the internal Steam folder
+    pub from: PathBuf,
+    pub to: PathBuf,
+    /// Remove the game from `from` once the copy is verified
+    pub delete_source: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Copying,
+    Verifying,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Progress {
+    pub stage: Stage,
+    pub done: u64,
+    pub total: u64,
+}
+
+impl Progress {
+    /// e.g. `Copying 3.2 GB of 10.1 GB (31%)`
+    pub fn summary(&self) -> String {
+        let stage = match self.stage {
+            Stage::Copying => "Copying",
+            Stage::Verifying => "Verifying",
+        };
+        let percent = (self.done * 100).checked_div(self.total).unwrap_or(100);
+        format!(
+            "{stage} {} of {} ({percent}%)",
+            scanning::format_size(self.done),
+            scanning::format_size(self.total)
+        )
+    }
+}
+
+/// A library a game can be sent to
+#[derive(Debug, Clone)]
+pub struct Target {
+    pub name: String,
+    /// The library's root, it has a steamapps folder
+    pub path: PathBuf,
+    /// The card's UUID, None for the internal drive
+    pub card_uuid: Option<String>,
+}
+
+/// The libraries a game on the card can be sent to, the other inserted cards and the internal drive
+pub fn targets(list: &[Card], from_uuid: &str) -> Vec<Target> {
+    let mut targets: Vec<Target> = scanning::discover_cards()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|scanned| {
+            let card = &list[crate::identity::find_card(list, &scanned)?];
+            Some(Target {
+                name: card.name.clone(),
+                path: scanned.card_path,
+                card_uuid: Some(card.uuid.clone()),
+            })
+        })
+        .filter(|target| target.card_uuid.as_deref() != Some(from_uuid))
+        .collect();
+
+    if let Some(path) = steam::root().filter(|path| path.join("steamapps").is_dir()) {
+        targets.push(Target {
+            name: String::from("Internal Drive"),
+            path,
+            card_uuid: None,
+        });
+    }
+
+    targets
+}
+
+/// Copies the game's folder and then its appmanifest to the other library, verifies the copy,
+/// and removes the original if it's a move. Files already copied by an interrupted run are skipped
+pub fn run(transfer: &Transfer, mut progress: impl FnMut(Progress)) -> Result<(), Error> {
+    if steam::running() {
+        return Err(Error::SteamRunning {
+            doing: "moving games so its library isn't corrupted",
+        });
+    }
+
+    let manifest_name = format!("appmanifest_{}.acf", transfer.appid);
+    let source_dir = transfer.from.join("steamapps/common").join(&transfer.game);
+    let source_manifest = transfer.from.join("steamapps").join(&manifest_name);
+    let destination_steamapps = transfer.to.join("steamapps");
+    let destination_dir = destination_steamapps.join("common").join(&transfer.game);
+    let destination_manifest = destination_steamapps.join(&manifest_name);
+
+    if !source_dir.is_dir() || !source_manifest.is_file() {
+        return Err(Error::Invalid(format!(
+            "{} isn't installed there",
+            transfer.game
+        )));
+    }
+    if !destination_steamapps.is_dir() {
+        return Err(Error::Invalid(String::from(
+            "The destination isn't a Steam library yet, add it in Steam's storage settings first",
+        )));
+    }
+    // The manifest is copied last, so if it's there the game was already fully copied
+    if destination_manifest.exists() {
+        return Err(Error::Invalid(format!(
+            "{} is already installed there",
+            transfer.game
+        )));
+    }
+
+    let tree = list_tree(&source_dir)?;
+    let files = &tree.files;
+    let total: u64 = files.iter().map(|(_, size)| size).sum();
+    let already_copied: u64 = files
+        .iter()
+        .map(|(path, size)| copied_size(&destination_dir.join(path), *size))
+        .sum();
+
+    let needed = total - already_copied;
+    let free = scanning::free_space(&transfer.to)?;
+    if needed > free {
+        return Err(Error::NotEnoughSpace { needed, free });
+    }
+
+    let mut reporter = Reporter::new(Stage::Copying, total, &mut progress);
+    reporter.add(already_copied);
+    for dir in &tree.dirs {
+        let destination = destination_dir.join(dir);
+        fs::create_dir_all(&destination).map_err(Error::io("create", &destination))?;
+    }
+    for (link, target) in &tree.links {
+        copy_link(target, &destination_dir.join(link))?;
+    }
+    for (path, size) in files {
+        copy_file(
+            &source_dir.join(path),
+            &destination_dir.join(path),
+            *size,
+            &mut reporter,
+        )?;
+    }
+    reporter.finish();
+
+    // The original is only removed once every folder, link and file is at the destination
+    let mut reporter = Reporter::new(Stage::Verifying, total, &mut progress);
+    for dir in &tree.dirs {
+        if !destination_dir.join(dir).is_dir() {
+            return Err(Error::Invalid(format!(
+                "The folder {} is missing from the copy, try again to copy it again",
+                dir.display()
+            )));
+        }
+    }
+    for (link, target) in &tree.links {
+        if fs::read_link(destination_dir.join(link)).ok().as_ref() != Some(target) {
+            return Err(Error::Invalid(format!(
+                "The link {} didn't copy correctly, try again to copy it again",
+                link.display()
+            )));
+        }
+    }
+    for (path, size) in files {
+        let source = source_dir.join(path);
+        let destination = destination_dir.join(path);
+        let source_hash = snapshot::hash_file(&source);
+        let matches = fs::metadata(&destination).is_ok_and(|metadata| metadata.len() == *size)
+            && source_hash.is_some()
+            && source_hash == snapshot::hash_file(&destination);
+        if !matches {
+            // Removed so the next try copies it again
+            let _ = fs::remove_file(&destination);
+            return Err(Error::Invalid(format!(
+                "{} didn't copy correctly, try again to copy it again",
+                path.display()
+            )));
+        }
+        reporter.add(*size);
+    }
+    reporter.finish();
+
+    fs::copy(&source_manifest, &destination_manifest)
+        .map_err(Error::io("copy", &source_manifest))?;
+
+    if transfer.delete_source {
+        fs::remove_dir_all(&source_dir)
+            .and_then(|_| fs::remove_file(&source_manifest))
+            .map_err(Error::io("remove the original of", &source_dir))?;
+    }
+
+    Ok(())
+}
+
+/// Everything in a game's folder, paths are inside the folder.
+/// They're kept as paths rather than strings so names that aren't UTF-8 copy too
+struct Tree {
+    /// Every file and its size
+    files: Vec<(PathBuf, u64)>,
+    /// Symlinks and where they point, native Linux games use them for their libraries
+    links: Vec<(PathBuf, PathBuf)>,
+    /// Every folder, so empty ones are copied too
+    dirs: Vec<PathBuf>,
+}
+
+/// Lists the game's folder for copying, unlike `snapshot::list_files` it keeps symlinks and folders
+/// and fails on anything it can't read, since a move removes the original afterwards
+fn list_tree(game_dir: &Path) -> Result<Tree, Error> {
+    let mut tree = Tree {
+        files: vec![],
+        links: vec![],
+        dirs: vec![],
+    };
+    add_to_tree(game_dir, game_dir, &mut tree)?;
+    tree.files.sort();
+    Ok(tree)
+}
+
+fn add_to_tree(root: &Path, dir: &Path, tree: &mut Tree) -> Result<(), Error> {
+    for entry in fs::read_dir(dir).map_err(Error::io("read", dir))? {
+        let path = entry.map_err(Error::io("read", dir))?.path();
+        // Not followed, a link is copied as a link
+        let metadata = fs::symlink_metadata(&path).map_err(Error::io("read", &path))?;
+        let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+
+        if metadata.file_type().is_symlink() {
+            let target = fs::read_link(&path).map_err(Error::io("read", &path))?;
+            tree.links.push((relative, target));
+        } else if metadata.is_dir() {
+            tree.dirs.push(relative);
+            add_to_tree(root, &path, tree)?;
+        } else if metadata.is_file() {
+            tree.files.push((relative, metadata.len()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Makes the same symlink at the destination, replacing one left pointing somewhere else
+fn copy_link(target: &Path, destination: &Path) -> Result<(), Error> {
+    let error = Error::io("create the link", destination);
+    if fs::read_link(destination).is_ok_and(|existing| existing == target) {
+        return Ok(());
+    }
+    if fs::symlink_metadata(destination).is_ok() {
+        fs::remove_file(destination).map_err(Error::io("replace", destination))?;
+    }
+    if let Some(dir) = destination.parent() {
+        fs::create_dir_all(dir).map_err(Error::io("create", dir))?;
+    }
+    symlink(target, destination).map_err(error)
+}
+
+/// How much of the file is already at the destination, a finished copy or a partial one
+fn copied_size(destination: &Path, size: u64) -> u64 {
+    match fs::metadata(destination) {
+        Ok(metadata) if metadata.len() == size => size,
+        _ => fs::metadata(part_path(destination)).map_or(0, |metadata| metadata.len().min(size)),
+    }
+}
+
+fn part_path(destination: &Path) -> PathBuf {
+    let mut name = destination.as_os_str().to_os_string();
+    name.push(PART_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Copies into a part file, carrying on from the end of one left by an earlier run, and renames it once it's complete
+fn copy_file(
+    source: &Path,
+    destination: &Path,
+    size: u64,
+    reporter: &mut Reporter,
+) -> Result<(), Error> {
+    if fs::metadata(destination).is_ok_and(|metadata| metadata.len() == size) {
+        return Ok(());
+    }
+    let error = |e: std::io::Error| Error::io("copy", source)(e);
+
+    if let Some(dir) = destination.parent() {
+        fs::create_dir_all(dir).map_err(error)?;
+    }
+
+    let part = part_path(destination);
+    let mut output = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&part)
+        .map_err(error)?;
+    let mut offset = output.metadata().map_err(error)?.len();
+    if offset > size {
+        output.set_len(0).map_err(error)?;
+        reporter.remove(offset.min(size));
+        offset = 0;
+    }
+
+    let mut input = File::open(source).map_err(error)?;
+    input.seek(SeekFrom::Start(offset)).map_err(error)?;
+
+    let mut buffer = vec![0; BUFFER_SIZE];
+    loop {
+        let read = input.read(&mut buffer).map_err(error)?;
+        if read == 0 {
+            break;
+        }
+        output.write_all(&buffer[..read]).map_err(error)?;
+        reporter.add(read as u64);
+    }
+    output.sync_all().map_err(error)?;
+
+    // Keeps the executable bits of native Linux games
+    if let Ok(metadata) = fs::metadata(source) {
+        let _ = fs::set_permissions(&part, metadata.permissions());
+    }
+    fs::rename(&part, destination).map_err(error)
+}
+
+/// Counts the bytes done and calls the progress callback every `PROGRESS_INTERVAL`
+struct Reporter<'a> {
+    progress: Progress,
+    callback: &'a mut dyn FnMut(Progress),
+    last_report: Instant,
+}
+
+impl<'a> Reporter<'a> {
+    fn new(stage: Stage, total: u64, callback: &'a mut dyn FnMut(Progress)) -> Self {
+        callback(Progress {
+            stage,
+            done: 0,
+            total,
+        });
+        Reporter {
+            progress: Progress {
+                stage,
+                done: 0,
+                total,
+            },
+            callback,
+            last_report: Instant::now(),
+        }
+    }
+
+    fn add(&mut self, bytes: u64) {
+        self.progress.done += bytes;
+        if self.last_report.elapsed() >= PROGRESS_INTERVAL {
+            self.finish();
+        }
+    }
+
+    fn remove(&mut self, bytes: u64) {
+        self.progress.done = self.progress.done.saturating_sub(bytes);
+    }
+
+    fn finish(&mut self) {
+        (self.callback)(self.progress);
+        self.last_report = Instant::now();
+    }
+}
+
+/// Updates the saved cards after a transfer, the game is added to the card it went to
+/// and taken off the one it came from if it was moved. The favourite and note go with it
+pub fn record(
+    list: &mut [Card],
+    transfer: &Transfer,
+    from_uuid: Option<&str>,
+    to_uuid: Option<&str>,
+) {
+    let game = from_uuid
+        .and_then(|uuid| list.iter().find(|card| card.uuid == uuid))
+        .and_then(|card| card.games.iter().find(|game| game.name == transfer.game))
+        .cloned()
+        .unwrap_or_else(|| Game {
+            name: transfer.game.clone(),
+            appid: Some(transfer.appid),
+            ..Default::default()
+        });
+
+    if let Some(card) = to_uuid.and_then(|uuid| list.iter_mut().find(|card| card.uuid == uuid)) {
+        if !card.games.iter().any(|existing| existing.name == game.name) {
+            card.games.push(game);
+            card.games
+                .sort_by_key(|game| game.name.to_ascii_lowercase());
+        }
+    }
+
+    if transfer.delete_source {
+        if let Some(card) =
+            from_uuid.and_then(|uuid| list.iter_mut().find(|card| card.uuid == uuid))
+        {
+            card.games.retain(|game| game.name != transfer.game);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::error::Error;
+use crate::scanning::{self, Card, Library};
+use crate::steam;
+use std::fs;
+use std::path::PathBuf;
+
+/// Everything that goes when one game is uninstalled
+#[derive(Debug, Clone)]
+pub struct Removal {
+    pub library: Library,
+    pub game: String,
+    /// The game's folder, its appmanifest and its shadercache folder, the ones that exist.
+    /// The Proton prefix in compatdata is kept since it holds the game's saves, afterwards it's listed with the leftovers
+    pub paths: Vec<PathBuf>,
+    /// How much space removing `paths` frees
+    pub bytes: u64,
+}
+
+impl Removal {
+    /// Heroic and Lutris keep their own record of where the game is installed, which still points at the deleted folder
+    pub fn launcher_warning(&self) -> Option<String> {
+        let launcher = match self.library {
+            Library::Steam => return None,
+            Library::Lutris => "Lutris",
+            Library::Heroic => "Heroic",
+        };
+
+        Some(format!(
+            "{launcher} still lists {} as installed, remove it there too",
+            self.game
+        ))
+    }
+}
+
+/// Works out what uninstalling each game on the card would remove and how much space it frees.
+/// The card has to be inserted and mounted at its `mount_path`
+pub fn plan(card: &Card) -> Vec<Removal> {
+    let steamapps = card.mount_path.join("steamapps");
+    let mut removals = vec![];
+
+    for library in [Library::Steam, Library::Lutris, Library::Heroic] {
+        for game in card.library_games(library) {
+            let mut paths: Vec<PathBuf> = card.game_path(library, game).into_iter().collect();
+            if let (Library::Steam, Some(appid)) = (library, game.appid) {
+                paths.push(steamapps.join(format!("appmanifest_{appid}.acf")));
+                paths.push(steamapps.join("shadercache").join(appid.to_string()));
+            }
+            paths.retain(|path| path.exists());
+
+            let bytes = paths
+                .iter()
+                .map(|path| match fs::metadata(path) {
+                    Ok(metadata) if metadata.is_dir() => scanning::dir_size(path),
+                    Ok(metadata) => metadata.len(),
+                    Err(_) => 0,
+                })
+                .sum();
+
+            removals.push(Removal {
+                library,
+                game: game.name.clone(),
+                paths,
+                bytes,
+            });
+        }
+    }
+
+    // Biggest first, they're the ones worth removing
+    removals.sort_by_key(|removal| std::cmp::Reverse(removal.bytes));
+    removals
+}
+
+/// Deletes everything in the removals, stops at the first path that can't be removed.
+/// Refuses to touch Steam games while Steam is running
+pub fn run(removals: &[Removal]) -> Result<(), Error> {
+    let has_steam_games = removals
+        .iter()
+        .any(|removal| removal.library == Library::Steam);
+    if has_steam_games && steam::running() {
+        return Err(Error::SteamRunning {
+            doing: "uninstalling games so its library isn't corrupted",
+        });
+    }
+
+    for removal in removals {
+        for path in &removal.paths {
+            let result = if path.is_dir() {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
+            };
+            result.map_err(Error::io("remove", path))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Takes the uninstalled games off the saved card, only the ones that are fully gone if `run` stopped partway
+pub fn record(card: &mut Card, removals: &[Removal]) {
+    for removal in removals
+        .iter()
+        .filter(|removal| removal.paths.iter().all(|path| !path.exists()))
+    {
+        if let Some(games) = card.library_games_mut(removal.library) {
+            games.retain(|game| game.name != removal.game);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+mod appinfo;
+mod binary;
+mod files;
+#[cfg(test)]
+mod tests;
+mod text;
+
+pub use appinfo::{read_appinfo, AppInfo, AppInfoFile};
+pub use binary::{append_binary, parse_binary, write_binary};
+pub use files::{AppManifest, LibraryFolder, LocalApp, LocalConfig, ShortcutEntry};
+pub use text::{parse_text, write_text};
+
+/// A value in one of Valve's KeyValues files, either a string, a number or a table of more keys
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    /// A 32 bit number, only binary files have them, e.g. a shortcut's app ID or last played time
+    Int(u32),
+    Float(f32),
+    /// A 64 bit number from a binary file, appinfo.vdf uses them for some IDs
+    UInt64(u64),
+    Int64(i64),
+    /// The keys in the order they're in the file, keys can repeat
+    Table(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// The first value under the key, keys are compared ignoring case like Steam does
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Table(entries) => entries
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Follows a path of keys down through the tables, e.g. `["Software", "Valve", "Steam"]`
+    pub fn get_path(&self, path: &[&str]) -> Option<&Value> {
+        path.iter().try_fold(self, |value, key| value.get(key))
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value as a number, text files keep their numbers in strings
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::String(value) => value.trim().parse().ok(),
+            Value::Int(value) => Some(u64::from(*value)),
+            Value::UInt64(value) => Some(*value),
+            Value::Int64(value) => u64::try_from(*value).ok(),
+            Value::Float(_) | Value::Table(_) => None,
+        }
+    }
+
+    /// The table's entries, empty for anything else
+    pub fn entries(&self) -> &[(String, Value)] {
+        match self {
+            Value::Table(entries) => entries,
+            _ => &[],
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use super::binary::Reader;
+use super::Value;
+use crate::Error;
+
+/// The first four bytes of appinfo.vdf, they change whenever Steam changes the layout.
+/// v28 added a second checksum to each app, v29 moved the keys into a string table
+const MAGIC_V27: u32 = 0x0756_4427;
+const MAGIC_V28: u32 = 0x0756_4428;
+const MAGIC_V29: u32 = 0x0756_4429;
+
+/// Steam's cache of every app's store info, `appcache/appinfo.vdf`
+#[derive(Debug)]
+pub struct AppInfoFile<'a> {
+    /// 27, 28 or 29
+    pub version: u32,
+    pub universe: u32,
+    pub apps: Vec<AppInfo<'a>>,
+    /// The keys of v29 files
+    strings: Vec<String>,
+}
+
+/// One app's entry in appinfo.vdf, its keys are only parsed when they're asked for
+#[derive(Debug)]
+pub struct AppInfo<'a> {
+    pub appid: u32,
+    pub info_state: u32,
+    /// As a unix timestamp
+    pub last_updated: u32,
+    pub change_number: u32,
+    data: &'a [u8],
+}
+
+/// Reads appinfo.vdf's header and where each app's data is. The file is usually tens of megabytes
+/// so an app's keys are only parsed by `AppInfoFile::data`
+pub fn read_appinfo(input: &[u8]) -> Result<AppInfoFile<'_>, Error> {
+    let mut reader = Reader::new(input, None);
+
+    let magic = u32::from_le_bytes(reader.bytes()?);
+    let version = match magic {
+        MAGIC_V27 => 27,
+        MAGIC_V28 => 28,
+        MAGIC_V29 => 29,
+        magic => {
+            return Err(Error::vdf(format!(
+                "Unknown appinfo.vdf version {magic:#010x}"
+            )))
+        }
+    };
+    let universe = u32::from_le_bytes(reader.bytes()?);
+
+    let strings = if version >= 29 {
+        let offset = i64::from_le_bytes(reader.bytes()?);
+        let offset = usize::try_from(offset)
+            .ok()
+            .filter(|offset| *offset < input.len())
+            .ok_or_else(|| {
+                Error::vdf(format!(
+                    "The string table offset {offset} is outside the file"
+                ))
+            })?;
+        read_string_table(&input[offset..])?
+    } else {
+        vec![]
+    };
+
+    let mut apps = vec![];
+    loop {
+        let appid = u32::from_le_bytes(reader.bytes()?);
+        if appid == 0 {
+            break;
+        }
+
+        let size = u32::from_le_bytes(reader.bytes()?) as usize;
+        let end = reader.position + size;
+        let info_state = u32::from_le_bytes(reader.bytes()?);
+        let last_updated = u32::from_le_bytes(reader.bytes()?);
+        // The access token and the text data's SHA-1
+        reader.skip(8 + 20)?;
+        let change_number = u32::from_le_bytes(reader.bytes()?);
+        if version >= 28 {
+            // The binary data's SHA-1
+            reader.skip(20)?;
+        }
+
+        let data = input.get(reader.position..end).ok_or_else(|| {
+            Error::vdf(format!("App {appid}'s data runs past the end of the file"))
+        })?;
+        reader.position = end;
+
+        apps.push(AppInfo {
+            appid,
+            info_state,
+            last_updated,
+            change_number,
+            data,
+        });
+    }
+
+    Ok(AppInfoFile {
+        version,
+        universe,
+        apps,
+        strings,
+    })
+}
+
+fn read_string_table(input: &[u8]) -> Result<Vec<String>, Error> {
+    let mut reader = Reader::new(input, None);
+    let count = u32::from_le_bytes(reader.bytes()?);
+    (0..count).map(|_| reader.string()).collect()
+}
+
+impl AppInfoFile<'_> {
+    /// Parses the app's keys, the top level is a table holding an "appinfo" table
+    pub fn data(&self, app: &AppInfo) -> Result<Value, Error> {
+        let strings = (self.version >= 29).then_some(&self.strings[..]);
+        let mut reader = Reader::new(app.data, strings);
+        reader
+            .entries(false)
+            .map(Value::Table)
+            .map_err(|e| Error::vdf(format!("App {}: {e}", app.appid)))
+    }
+
+    /// The app's name from its store info
+    pub fn name(&self, app: &AppInfo) -> Option<String> {
+        let data = self.data(app).ok()?;
+        let name = data.get_path(&["appinfo", "common", "name"])?.as_str()?;
+        Some(name.to_string())
+    }
+}
@@ -0,0 +1,206 @@
+use super::Value;
+use crate::Error;
+
+/// The type bytes in front of each key in a binary file
+const TABLE: u8 = 0x00;
+const STRING: u8 = 0x01;
+const INT: u8 = 0x02;
+const FLOAT: u8 = 0x03;
+const UINT64: u8 = 0x07;
+const END: u8 = 0x08;
+const INT64: u8 = 0x0a;
+
+/// Parses a binary KeyValues file like shortcuts.vdf, the top level keys are returned as a table
+pub fn parse_binary(input: &[u8]) -> Result<Value, Error> {
+    let mut reader = Reader::new(input, None);
+    let entries = reader.entries(false)?;
+    Ok(Value::Table(entries))
+}
+
+/// Writes the top level table of keys in the binary format, the reverse of `parse_binary`
+pub fn write_binary(value: &Value) -> Vec<u8> {
+    let mut output = vec![];
+    write_entries(value.entries(), &mut output);
+    output
+}
+
+/// Adds the entries to the end of a top level table without writing the rest of the file again,
+/// so strings that aren't UTF-8 stay exactly as they were. The table is added if the file doesn't have it
+pub fn append_binary(
+    input: &[u8],
+    table: &str,
+    entries: &[(String, Value)],
+) -> Result<Vec<u8>, Error> {
+    let mut reader = Reader::new(input, None);
+    let mut output = vec![];
+
+    loop {
+        let kind = match input.get(reader.position) {
+            Some(&kind) if kind != END => kind,
+            // The end of the file without finding the table
+            end => {
+                output.extend(&input[..reader.position]);
+                output.push(TABLE);
+                write_string(table, &mut output);
+                write_entries(entries, &mut output);
+                output.extend(&input[reader.position..]);
+                if end.is_none() {
+                    output.push(END);
+                }
+                return Ok(output);
+            }
+        };
+        reader.position += 1;
+
+        let key = reader.key()?;
+        if kind == TABLE && key.eq_ignore_ascii_case(table) {
+            reader.entries(true)?;
+            // In front of the table's end byte
+            let end = reader.position - 1;
+            output.extend(&input[..end]);
+            for (key, value) in entries {
+                write_entry(key, value, &mut output);
+            }
+            output.extend(&input[end..]);
+            return Ok(output);
+        }
+        reader.value(kind, &key)?;
+    }
+}
+
+fn write_entries(entries: &[(String, Value)], output: &mut Vec<u8>) {
+    for (key, value) in entries {
+        write_entry(key, value, output);
+    }
+    output.push(END);
+}
+
+fn write_entry(key: &str, value: &Value, output: &mut Vec<u8>) {
+    let kind = match value {
+        Value::Table(_) => TABLE,
+        Value::String(_) => STRING,
+        Value::Int(_) => INT,
+        Value::Float(_) => FLOAT,
+        Value::UInt64(_) => UINT64,
+        Value::Int64(_) => INT64,
+    };
+    output.push(kind);
+    write_string(key, output);
+
+    match value {
+        Value::Table(entries) => write_entries(entries, output),
+        Value::String(value) => write_string(value, output),
+        Value::Int(value) => output.extend(value.to_le_bytes()),
+        Value::Float(value) => output.extend(value.to_le_bytes()),
+        Value::UInt64(value) => output.extend(value.to_le_bytes()),
+        Value::Int64(value) => output.extend(value.to_le_bytes()),
+    }
+}
+
+fn write_string(value: &str, output: &mut Vec<u8>) {
+    output.extend(value.as_bytes());
+    output.push(0);
+}
+
+/// Reads binary KeyValues from a slice of a file
+pub(super) struct Reader<'a> {
+    input: &'a [u8],
+    pub position: usize,
+    /// appinfo.vdf from v29 on stores each key once in a table at the end of the file
+    /// and only the key's index in front of each value
+    strings: Option<&'a [String]>,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(input: &'a [u8], strings: Option<&'a [String]>) -> Self {
+        Reader {
+            input,
+            position: 0,
+            strings,
+        }
+    }
+
+    pub fn entries(&mut self, nested: bool) -> Result<Vec<(String, Value)>, Error> {
+        let mut entries = vec![];
+
+        loop {
+            let kind = match self.input.get(self.position) {
+                Some(&kind) => kind,
+                // Some files leave off the top level's end byte
+                None if !nested => return Ok(entries),
+                None => return Err(Error::vdf("The file ended inside a table")),
+            };
+            self.position += 1;
+            if kind == END {
+                return Ok(entries);
+            }
+
+            let key = self.key()?;
+            let value = self.value(kind, &key)?;
+            entries.push((key, value));
+        }
+    }
+
+    /// Reads the value after a key, `kind` is the type byte in front of the key
+    fn value(&mut self, kind: u8, key: &str) -> Result<Value, Error> {
+        Ok(match kind {
+            TABLE => Value::Table(self.entries(true)?),
+            STRING => Value::String(self.string()?),
+            INT => Value::Int(u32::from_le_bytes(self.bytes()?)),
+            FLOAT => Value::Float(f32::from_le_bytes(self.bytes()?)),
+            UINT64 => Value::UInt64(u64::from_le_bytes(self.bytes()?)),
+            INT64 => Value::Int64(i64::from_le_bytes(self.bytes()?)),
+            kind => {
+                return Err(Error::vdf(format!(
+                    "Unknown value type {kind:#04x} for {key} at byte {}",
+                    self.position
+                )))
+            }
+        })
+    }
+
+    fn key(&mut self) -> Result<String, Error> {
+        match self.strings {
+            Some(strings) => {
+                let index = u32::from_le_bytes(self.bytes()?) as usize;
+                strings
+                    .get(index)
+                    .cloned()
+                    .ok_or_else(|| Error::vdf(format!("Key {index} isn't in the string table")))
+            }
+            None => self.string(),
+        }
+    }
+
+    pub fn string(&mut self) -> Result<String, Error> {
+        let rest = self.input.get(self.position..).unwrap_or_default();
+        let length = rest
+            .iter()
+            .position(|&byte| byte == 0)
+            .ok_or_else(|| Error::vdf(format!("Unterminated string at byte {}", self.position)))?;
+        self.position += length + 1;
+        Ok(String::from_utf8_lossy(&rest[..length]).into_owned())
+    }
+
+    /// The next N bytes, for the numbers
+    pub fn bytes<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let bytes = self
+            .input
+            .get(self.position..self.position + N)
+            .ok_or_else(|| Error::vdf(format!("The file ended at byte {}", self.input.len())))?;
+        self.position += N;
+        Ok(bytes.try_into().unwrap_or([0; N]))
+    }
+
+    /// Skips over bytes the caller doesn't need, like checksums
+    pub fn skip(&mut self, count: usize) -> Result<(), Error> {
+        if self.position + count > self.input.len() {
+            return Err(Error::vdf(format!(
+                "The file ended at byte {}",
+                self.input.len()
+            )));
+        }
+        self.position += count;
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use super::{parse_text, Value};
+use crate::Error;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// The parts of a Steam appmanifest_*.acf file the scanner uses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppManifest {
+    pub appid: u32,
+    /// The game's name in the store
+    pub name: Option<String>,
+    /// The game's folder under steamapps/common
+    pub install_dir: String,
+    pub last_updated: Option<u64>,
+    pub build: Option<u32>,
+    pub state_flags: Option<u32>,
+    /// How many bytes Steam expects the install to take up
+    pub size_on_disk: Option<u64>,
+}
+
+impl AppManifest {
+    pub fn parse(input: &str) -> Result<AppManifest, Error> {
+        let file = parse_text(input)?;
+        let state = file
+            .get("AppState")
+            .ok_or_else(|| Error::vdf("No AppState in the manifest"))?;
+        let number = |key: &str| state.get(key).and_then(Value::as_u64);
+
+        Ok(AppManifest {
+            appid: number("appid")
+                .and_then(|appid| u32::try_from(appid).ok())
+                .ok_or_else(|| Error::vdf("The manifest has no appid"))?,
+            name: state
+                .get("name")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            install_dir: state
+                .get("installdir")
+                .and_then(Value::as_str)
+                .ok_or_else(|| Error::vdf("The manifest has no installdir"))?
+                .to_string(),
+            last_updated: number("LastUpdated"),
+            build: number("buildid").and_then(|build| u32::try_from(build).ok()),
+            state_flags: number("StateFlags").and_then(|flags| u32::try_from(flags).ok()),
+            size_on_disk: number("SizeOnDisk"),
+        })
+    }
+}
+
+/// One of the Steam libraries in libraryfolders.vdf
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LibraryFolder {
+    /// The folder holding the library's steamapps folder
+    pub path: PathBuf,
+    pub label: String,
+    /// The app IDs Steam thinks are installed there, empty in the old format
+    pub apps: Vec<u32>,
+}
+
+impl LibraryFolder {
+    /// Reads every library in libraryfolders.vdf, both the current format and the old one
+    /// where each library is just a numbered path
+    pub fn parse_all(input: &str) -> Result<Vec<LibraryFolder>, Error> {
+        let file = parse_text(input)?;
+        let folders = file
+            .get("libraryfolders")
+            .ok_or_else(|| Error::vdf("No libraryfolders in the file"))?;
+
+        Ok(folders
+            .entries()
+            .iter()
+            .filter(|(key, _)| key.parse::<u32>().is_ok())
+            .filter_map(|(_, folder)| match folder {
+                Value::String(path) => Some(LibraryFolder {
+                    path: PathBuf::from(path),
+                    label: String::new(),
+                    apps: vec![],
+                }),
+                Value::Table(_) => Some(LibraryFolder {
+                    path: PathBuf::from(folder.get("path")?.as_str()?),
+                    label: folder
+                        .get("label")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                    apps: folder
+                        .get("apps")
+                        .map_or(&[][..], Value::entries)
+                        .iter()
+                        .filter_map(|(appid, _)| appid.parse().ok())
+                        .collect(),
+                }),
+                _ => None,
+            })
+            .collect())
+    }
+}
+
+/// What Steam's user config knows about one app
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LocalApp {
+    /// As a unix timestamp, None if it was never played
+    pub last_played: Option<u64>,
+    /// In minutes
+    pub playtime: Option<u64>,
+}
+
+/// The parts of a Steam user's localconfig.vdf the app uses
+#[derive(Debug, Clone, Default)]
+pub struct LocalConfig {
+    /// Keyed by app ID
+    pub apps: HashMap<u32, LocalApp>,
+}
+
+impl LocalConfig {
+    pub fn parse(input: &str) -> Result<LocalConfig, Error> {
+        let config = parse_text(input)?;
+        let apps = config
+            .get_path(&["UserLocalConfigStore", "Software", "Valve", "Steam", "apps"])
+            .map_or(&[][..], Value::entries);
+
+        let apps = apps
+            .iter()
+            .filter_map(|(appid, app)| {
+                // Zero means never
+                let number = |key: &str| app.get(key)?.as_u64().filter(|value| *value > 0);
+                Some((
+                    appid.parse().ok()?,
+                    LocalApp {
+                        last_played: number("LastPlayed"),
+                        playtime: number("Playtime"),
+                    },
+                ))
+            })
+            .collect();
+
+        Ok(LocalConfig { apps })
+    }
+}
+
+/// A non-Steam game in a Steam user's shortcuts.vdf
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortcutEntry {
+    pub appid: Option<u32>,
+    pub app_name: String,
+    pub exe: String,
+    pub start_dir: String,
+    pub launch_options: String,
+}
+
+impl ShortcutEntry {
+    /// The shortcuts in a parsed shortcuts.vdf, older Steam versions wrote the keys in lowercase
+    pub fn list(file: &Value) -> Vec<ShortcutEntry> {
+        file.get("shortcuts")
+            .map_or(&[][..], Value::entries)
+            .iter()
+            .map(|(_, shortcut)| {
+                let string = |key: &str| {
+                    shortcut
+                        .get(key)
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                ShortcutEntry {
+                    appid: shortcut
+                        .get("appid")
+                        .and_then(Value::as_u64)
+                        .and_then(|appid| u32::try_from(appid).ok()),
+                    app_name: string("AppName"),
+                    exe: string("Exe"),
+                    start_dir: string("StartDir"),
+                    launch_options: string("LaunchOptions"),
+                }
+            })
+            .collect()
+    }
+}
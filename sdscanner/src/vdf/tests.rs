@@ -0,0 +1,287 @@
+use super::*;
+use std::path::PathBuf;
+
+/// Samples laid out like the files Steam writes on the Deck, in tests/fixtures/vdf
+macro_rules! fixture {
+    ($name:literal) => {
+        include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/vdf/",
+            $name
+        ))
+    };
+}
+
+fn fixture_text(bytes: &[u8]) -> &str {
+    std::str::from_utf8(bytes).unwrap()
+}
+
+#[test]
+fn reads_an_appmanifest() {
+    let manifest = AppManifest::parse(fixture_text(fixture!("appmanifest_1245620.acf"))).unwrap();
+
+    assert_eq!(
+        manifest,
+        AppManifest {
+            appid: 1245620,
+            name: Some(String::from("ELDEN RING")),
+            install_dir: String::from("ELDEN RING"),
+            last_updated: Some(1698231482),
+            build: Some(12479023),
+            state_flags: Some(4),
+            size_on_disk: Some(50298742614),
+        }
+    );
+}
+
+#[test]
+fn appmanifest_without_an_installdir_is_an_error() {
+    let result = AppManifest::parse("\"AppState\"\n{\n\t\"appid\"\t\t\"10\"\n}\n");
+    assert!(result.is_err());
+}
+
+#[test]
+fn reads_library_folders() {
+    let folders = LibraryFolder::parse_all(fixture_text(fixture!("libraryfolders.vdf"))).unwrap();
+
+    assert_eq!(folders.len(), 2);
+    assert_eq!(
+        folders[0].path,
+        PathBuf::from("/home/deck/.local/share/Steam")
+    );
+    assert_eq!(folders[0].apps, vec![228980, 1493710, 1628350]);
+    assert_eq!(folders[1].label, "Red Card");
+    assert_eq!(folders[1].apps, vec![1245620]);
+}
+
+#[test]
+fn reads_old_library_folders() {
+    let folders =
+        LibraryFolder::parse_all(fixture_text(fixture!("libraryfolders_old.vdf"))).unwrap();
+
+    assert_eq!(
+        folders,
+        vec![LibraryFolder {
+            path: PathBuf::from("/run/media/mmcblk0p1"),
+            label: String::new(),
+            apps: vec![],
+        }]
+    );
+}
+
+#[test]
+fn reads_localconfig_play_data() {
+    let config = LocalConfig::parse(fixture_text(fixture!("localconfig.vdf"))).unwrap();
+
+    assert_eq!(
+        config.apps[&1245620],
+        LocalApp {
+            last_played: Some(1699900011),
+            playtime: Some(5123),
+        }
+    );
+    assert_eq!(config.apps[&413150].playtime, Some(1740));
+    // Zeroes mean it was never played
+    assert_eq!(config.apps[&1091500], LocalApp::default());
+}
+
+#[test]
+fn text_comments_conditions_and_escapes() {
+    let config = parse_text(fixture_text(fixture!("localconfig.vdf"))).unwrap();
+    let steam = config
+        .get_path(&["UserLocalConfigStore", "Software", "Valve", "Steam"])
+        .unwrap();
+
+    assert_eq!(
+        steam.get("InstallConfigStore").and_then(Value::as_str),
+        Some(r"C:\Program Files (x86)\Steam")
+    );
+    assert_eq!(
+        steam
+            .get_path(&["apps", "1091500", "LaunchOptions"])
+            .and_then(Value::as_str),
+        Some(r#"PROTON_LOG=1 %command% "-skipStartScreen""#)
+    );
+    // Keys are matched ignoring case like Steam does
+    assert!(config.get("userlocalconfigstore").is_some());
+}
+
+#[test]
+fn text_round_trips() {
+    let original = parse_text(fixture_text(fixture!("appmanifest_1245620.acf"))).unwrap();
+    let written = write_text(&original);
+
+    assert_eq!(parse_text(&written).unwrap(), original);
+    assert!(written.starts_with("\"AppState\"\n{\n\t\"appid\"\t\t\"1245620\"\n"));
+}
+
+#[test]
+fn text_errors_have_line_numbers() {
+    assert_eq!(
+        parse_text("\"a\"\n{\n\t\"b\"\t\"c\n")
+            .unwrap_err()
+            .to_string(),
+        "Unclosed quote at line 4"
+    );
+    assert_eq!(
+        parse_text("\"a\"\t\"b\"\n}\n").unwrap_err().to_string(),
+        "Unexpected } at line 2"
+    );
+    assert_eq!(
+        parse_text("\"a\"\n}\n").unwrap_err().to_string(),
+        "a has no value at line 2"
+    );
+    assert!(parse_text("\"a\"\n{\n\t\"b\"\t\"c\"\n")
+        .unwrap_err()
+        .to_string()
+        .contains("ended inside a table"));
+}
+
+#[test]
+fn reads_shortcuts() {
+    let file = parse_binary(fixture!("shortcuts.vdf")).unwrap();
+    let shortcuts = ShortcutEntry::list(&file);
+
+    assert_eq!(shortcuts.len(), 2);
+    assert_eq!(shortcuts[0].app_name, "Heroic Games Launcher");
+    assert_eq!(shortcuts[0].appid, Some(0xD3A1B2C4));
+    assert_eq!(
+        shortcuts[0].launch_options,
+        "run com.heroicgameslauncher.hgl"
+    );
+    assert_eq!(
+        shortcuts[1].exe,
+        "\"/run/media/mmcblk0p1/Heroic/Celeste/Celeste.exe\""
+    );
+    assert_eq!(
+        file.get_path(&["shortcuts", "0", "tags", "0"])
+            .and_then(Value::as_str),
+        Some("Launchers")
+    );
+}
+
+#[test]
+fn binary_round_trips() {
+    let original = fixture!("shortcuts.vdf");
+    let file = parse_binary(original).unwrap();
+
+    assert_eq!(write_binary(&file), original);
+}
+
+#[test]
+fn appending_keeps_the_existing_bytes() {
+    // A shortcut whose name isn't UTF-8, written back as it was
+    let mut original = vec![0x00];
+    original.extend(b"shortcuts\0\x00" as &[u8]);
+    original.extend(b"0\0\x01AppName\0Caf\xe9\0\x08\x08\x08" as &[u8]);
+    let added = [(
+        String::from("1"),
+        Value::Table(vec![(
+            String::from("AppName"),
+            Value::String(String::from("Celeste")),
+        )]),
+    )];
+
+    let appended = append_binary(&original, "shortcuts", &added).unwrap();
+
+    assert!(appended.starts_with(&original[..original.len() - 2]));
+    assert!(appended.ends_with(b"\x08\x08"));
+    let shortcuts = ShortcutEntry::list(&parse_binary(&appended).unwrap());
+    assert_eq!(shortcuts.len(), 2);
+    assert_eq!(shortcuts[1].app_name, "Celeste");
+}
+
+#[test]
+fn appending_adds_a_missing_table() {
+    let added = [(String::from("0"), Value::Table(vec![]))];
+    let appended = append_binary(&[], "shortcuts", &added).unwrap();
+
+    assert_eq!(
+        parse_binary(&appended).unwrap(),
+        Value::Table(vec![(
+            String::from("shortcuts"),
+            Value::Table(added.to_vec())
+        )])
+    );
+}
+
+#[test]
+fn binary_numbers_round_trip() {
+    let file = Value::Table(vec![(
+        String::from("numbers"),
+        Value::Table(vec![
+            (String::from("int"), Value::Int(u32::MAX)),
+            (String::from("float"), Value::Float(1.5)),
+            (String::from("uint64"), Value::UInt64(76561198000000000)),
+            (String::from("int64"), Value::Int64(-2914061842370431071)),
+        ]),
+    )]);
+
+    assert_eq!(parse_binary(&write_binary(&file)).unwrap(), file);
+}
+
+#[test]
+fn truncated_binary_is_an_error() {
+    let original = fixture!("shortcuts.vdf");
+
+    assert!(parse_binary(&original[..original.len() / 2]).is_err());
+    assert!(parse_binary(&[0x09, b'a', 0]).is_err());
+}
+
+fn check_appinfo(bytes: &[u8], version: u32) {
+    let appinfo = read_appinfo(bytes).unwrap();
+
+    assert_eq!(appinfo.version, version);
+    assert_eq!(appinfo.universe, 1);
+    assert_eq!(
+        appinfo.apps.iter().map(|app| app.appid).collect::<Vec<_>>(),
+        vec![1245620, 413150]
+    );
+    assert_eq!(appinfo.apps[0].last_updated, 1698231482);
+    assert_eq!(appinfo.apps[0].change_number, 21245620);
+    assert_eq!(
+        appinfo.name(&appinfo.apps[0]).as_deref(),
+        Some("ELDEN RING")
+    );
+    assert_eq!(
+        appinfo.name(&appinfo.apps[1]).as_deref(),
+        Some("Stardew Valley")
+    );
+
+    let data = appinfo.data(&appinfo.apps[0]).unwrap();
+    assert_eq!(
+        data.get_path(&["appinfo", "appid"]).and_then(Value::as_u64),
+        Some(1245620)
+    );
+    assert_eq!(
+        data.get_path(&["appinfo", "common", "parent"]),
+        Some(&Value::UInt64(0))
+    );
+}
+
+#[test]
+fn reads_appinfo_v27() {
+    check_appinfo(fixture!("appinfo_v27.vdf"), 27);
+}
+
+#[test]
+fn reads_appinfo_v28() {
+    check_appinfo(fixture!("appinfo_v28.vdf"), 28);
+}
+
+#[test]
+fn reads_appinfo_v29_string_table() {
+    check_appinfo(fixture!("appinfo_v29.vdf"), 29);
+}
+
+#[test]
+fn unknown_appinfo_version_is_an_error() {
+    // The version is the whole little endian magic at the start, 0x07564427 to 0x07564429
+    let mut bytes = fixture!("appinfo_v28.vdf").to_vec();
+    bytes[..4].copy_from_slice(&0x0756_4430_u32.to_le_bytes());
+
+    assert_eq!(
+        read_appinfo(&bytes).unwrap_err().to_string(),
+        "Unknown appinfo.vdf version 0x07564430"
+    );
+}
@@ -0,0 +1,168 @@
+use super::Value;
+use crate::Error;
+
+/// Parses a text KeyValues file like localconfig.vdf or an appmanifest, the top level keys are returned as a table
+pub fn parse_text(input: &str) -> Result<Value, Error> {
+    let mut tokens = Tokenizer {
+        chars: input.chars().peekable(),
+        line: 1,
+    };
+    let entries = parse_entries(&mut tokens, false)?;
+    Ok(Value::Table(entries))
+}
+
+/// Writes the top level table of keys in the text format, tab indented like Steam writes its files
+pub fn write_text(value: &Value) -> String {
+    let mut output = String::new();
+    write_text_entries(value.entries(), 0, &mut output);
+    output
+}
+
+fn write_text_entries(entries: &[(String, Value)], depth: usize, output: &mut String) {
+    let indent = "\t".repeat(depth);
+
+    for (key, value) in entries {
+        output.push_str(&format!("{indent}\"{}\"", escape(key)));
+        let value = match value {
+            Value::Table(entries) => {
+                output.push_str(&format!("\n{indent}{{\n"));
+                write_text_entries(entries, depth + 1, output);
+                output.push_str(&format!("{indent}}}\n"));
+                continue;
+            }
+            Value::String(value) => escape(value),
+            Value::Int(value) => value.to_string(),
+            Value::Float(value) => value.to_string(),
+            Value::UInt64(value) => value.to_string(),
+            Value::Int64(value) => value.to_string(),
+        };
+        output.push_str(&format!("\t\t\"{value}\"\n"));
+    }
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    String(String),
+    Open,
+    Close,
+}
+
+struct Tokenizer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+}
+
+impl Tokenizer<'_> {
+    fn next_token(&mut self) -> Result<Option<Token>, Error> {
+        loop {
+            let c = match self.chars.next() {
+                Some(c) => c,
+                None => return Ok(None),
+            };
+
+            match c {
+                '\n' => self.line += 1,
+                c if c.is_whitespace() => {}
+                '/' if self.chars.peek() == Some(&'/') => {
+                    // Comments run to the end of the line
+                    for c in self.chars.by_ref() {
+                        if c == '\n' {
+                            self.line += 1;
+                            break;
+                        }
+                    }
+                }
+                // Platform conditions like [$WIN32] after a value, they don't matter on the Deck
+                '[' => {
+                    for c in self.chars.by_ref() {
+                        if c == ']' {
+                            break;
+                        }
+                    }
+                }
+                '{' => return Ok(Some(Token::Open)),
+                '}' => return Ok(Some(Token::Close)),
+                '"' => return self.quoted().map(|s| Some(Token::String(s))),
+                c => {
+                    let mut value = String::from(c);
+                    while let Some(&c) = self.chars.peek() {
+                        if c.is_whitespace() || matches!(c, '"' | '{' | '}') {
+                            break;
+                        }
+                        value.push(c);
+                        self.chars.next();
+                    }
+                    return Ok(Some(Token::String(value)));
+                }
+            }
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String, Error> {
+        let mut value = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(value),
+                Some('\\') => match self.chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(c) => value.push(c),
+                    None => break,
+                },
+                Some(c) => {
+                    if c == '\n' {
+                        self.line += 1;
+                    }
+                    value.push(c);
+                }
+                None => break,
+            }
+        }
+        Err(Error::vdf(format!("Unclosed quote at line {}", self.line)))
+    }
+}
+
+fn parse_entries(tokens: &mut Tokenizer, nested: bool) -> Result<Vec<(String, Value)>, Error> {
+    let mut entries = vec![];
+
+    loop {
+        let key = match tokens.next_token()? {
+            Some(Token::String(key)) => key,
+            Some(Token::Close) if nested => return Ok(entries),
+            None if !nested => return Ok(entries),
+            Some(Token::Close) => {
+                return Err(Error::vdf(format!("Unexpected }} at line {}", tokens.line)))
+            }
+            Some(Token::Open) => {
+                return Err(Error::vdf(format!("Unexpected {{ at line {}", tokens.line)))
+            }
+            None => return Err(Error::vdf("The file ended inside a table")),
+        };
+
+        let value = match tokens.next_token()? {
+            Some(Token::String(value)) => Value::String(value),
+            Some(Token::Open) => Value::Table(parse_entries(tokens, true)?),
+            _ => {
+                return Err(Error::vdf(format!(
+                    "{key} has no value at line {}",
+                    tokens.line
+                )))
+            }
+        };
+        entries.push((key, value));
+    }
+}
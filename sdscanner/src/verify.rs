@@ -0,0 +1,198 @@
+use crate::error::Error;
+use crate::scanning::{self, Card, Library};
+use crate::snapshot::{self, GameSnapshot};
+use crate::vdf::AppManifest;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Zero byte files with these extensions are always suspicious, games don't ship empty executables or archives
+const NEVER_EMPTY_EXTENSIONS: [&str; 8] = ["exe", "dll", "so", "pak", "pck", "vpk", "bin", "cpk"];
+
+/// A game folder can be this much smaller than Steam's SizeOnDisk before it counts as missing files
+const STEAM_SIZE_TOLERANCE: f64 = 0.95;
+
+/// Steam's StateFlags for files it knows are missing or corrupt
+const STATE_FILES_MISSING: u32 = 32;
+const STATE_FILES_CORRUPT: u32 = 128;
+
+/// How many problems are listed for one game before the rest are summed up
+const MAX_LISTED_PROBLEMS: usize = 10;
+
+/// What a verify run found on one card
+#[derive(Debug, Clone, Default)]
+pub struct VerifyReport {
+    /// (library, game name, problems) for every game that was checked, empty problems means it's fine
+    pub games: Vec<(Library, String, Vec<String>)>,
+    /// Set if the card's filesystem itself looks damaged
+    pub filesystem_problem: Option<String>,
+    /// The snapshots couldn't be read or saved, the games were still checked
+    pub warnings: Vec<Error>,
+}
+
+impl VerifyReport {
+    pub fn suspect_count(&self) -> usize {
+        self.games
+            .iter()
+            .filter(|(_, _, problems)| !problems.is_empty())
+            .count()
+    }
+}
+
+/// Checks every game folder on the card against Steam's manifests and the snapshot from the last clean verify.
+/// Games that pass get a fresh snapshot. The card has to be inserted and mounted at its `mount_path`
+pub fn verify_card(card: &Card) -> VerifyReport {
+    let mut report = VerifyReport {
+        filesystem_problem: filesystem_problem(&card.mount_path),
+        ..Default::default()
+    };
+
+    let manifests = scanning::read_app_manifests(&card.mount_path.join("steamapps"));
+    let mut snapshots = snapshot::load(&card.uuid).unwrap_or_else(|e| {
+        report.warnings.push(e);
+        Default::default()
+    });
+
+    for library in [Library::Steam, Library::Lutris, Library::Heroic] {
+        for game in card.library_games(library) {
+            let game_dir = match card.game_path(library, game) {
+                Some(game_dir) => game_dir,
+                None => continue,
+            };
+            let key = snapshot::snapshot_key(library, &game.name);
+
+            let manifest = match library {
+                Library::Steam => manifests.get(&game.name),
+                _ => None,
+            };
+            let build = manifest.and_then(|manifest| manifest.build);
+
+            let mut problems = vec![];
+            if !game_dir.is_dir() {
+                problems.push(String::from("The game's folder is missing"));
+                report.games.push((library, game.name.clone(), problems));
+                continue;
+            }
+
+            let files = snapshot::list_files(&game_dir, false);
+
+            if let Some(manifest) = manifest {
+                check_manifest(manifest, &files, &mut problems);
+            }
+
+            // A snapshot from before a Steam update doesn't say anything about the new files
+            match snapshots.get(&key) {
+                Some(snapshot) if snapshot.build == build => {
+                    check_snapshot(snapshot, &files, &mut problems)
+                }
+                _ => check_empty_files(&files, &mut problems),
+            }
+
+            if problems.len() > MAX_LISTED_PROBLEMS {
+                let more = problems.len() - MAX_LISTED_PROBLEMS;
+                problems.truncate(MAX_LISTED_PROBLEMS);
+                problems.push(format!("...and {more} more"));
+            }
+
+            if problems.is_empty() {
+                snapshots.insert(key, GameSnapshot { build, files });
+            }
+            report.games.push((library, game.name.clone(), problems));
+        }
+    }
+
+    if let Err(e) = snapshot::save(&card.uuid, &snapshots) {
+        report.warnings.push(e);
+    }
+
+    report
+}
+
+/// The kernel switches a filesystem to read only when it finds errors on it
+fn filesystem_problem(mount_path: &Path) -> Option<String> {
+    let mounts = fs::read_to_string("/proc/mounts").ok()?;
+    let mount_path = mount_path.to_str()?;
+
+    let options = mounts.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let mount_point = fields.nth(1)?.replace("\\040", " ");
+        if mount_point == mount_path {
+            fields.nth(1)
+        } else {
+            None
+        }
+    })?;
+
+    if options.split(',').any(|option| option == "ro") {
+        Some(String::from(
+            "The card is mounted read only, which usually means the filesystem has errors. Run fsck on it from the desktop",
+        ))
+    } else {
+        None
+    }
+}
+
+fn check_manifest(
+    manifest: &AppManifest,
+    files: &[snapshot::FileEntry],
+    problems: &mut Vec<String>,
+) {
+    if let Some(state_flags) = manifest.state_flags {
+        if state_flags & STATE_FILES_MISSING != 0 {
+            problems.push(String::from("Steam reports files missing"));
+        }
+        if state_flags & STATE_FILES_CORRUPT != 0 {
+            problems.push(String::from("Steam reports files corrupt"));
+        }
+    }
+
+    if let Some(expected) = manifest.size_on_disk.filter(|size| *size > 0) {
+        let total: u64 = files.iter().map(|file| file.size).sum();
+        if (total as f64) < expected as f64 * STEAM_SIZE_TOLERANCE {
+            problems.push(format!(
+                "Only {} of the {} Steam installed",
+                scanning::format_size(total),
+                scanning::format_size(expected)
+            ));
+        }
+    }
+}
+
+fn check_snapshot(
+    snapshot: &GameSnapshot,
+    files: &[snapshot::FileEntry],
+    problems: &mut Vec<String>,
+) {
+    let current: HashMap<&str, u64> = files
+        .iter()
+        .map(|file| (file.path.as_str(), file.size))
+        .collect();
+
+    for file in &snapshot.files {
+        match current.get(file.path.as_str()) {
+            None => problems.push(format!("Missing: {}", file.path)),
+            Some(0) if file.size > 0 => problems.push(format!("Empty: {}", file.path)),
+            Some(size) if *size != file.size => problems.push(format!(
+                "Size changed: {} ({} to {})",
+                file.path,
+                scanning::format_size(file.size),
+                scanning::format_size(*size)
+            )),
+            Some(_) => {}
+        }
+    }
+}
+
+/// Without a snapshot to compare to, only empty files that should never be empty can be caught
+fn check_empty_files(files: &[snapshot::FileEntry], problems: &mut Vec<String>) {
+    for file in files.iter().filter(|file| file.size == 0) {
+        let extension = Path::new(&file.path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+
+        if extension.is_some_and(|extension| NEVER_EMPTY_EXTENSIONS.contains(&extension.as_str())) {
+            problems.push(format!("Empty: {}", file.path));
+        }
+    }
+}
@@ -1,77 +1,1204 @@
-use crate::scanning::{self, Card};
-use iced::widget::{column, container, row, text};
-use iced::{executor, window, Alignment, Application, Command, Element, Length, Theme};
+use iced::widget::{column, container, image, row, scrollable, text};
+use iced::{
+    clipboard, executor, subscription, window, Alignment, Application, Command, Element, Event,
+    Length, Subscription, Theme,
+};
+use sdscanner::artwork::{self, Thumbnail};
+use sdscanner::benchmark::{self, BenchmarkResult};
+use sdscanner::cleanup::{self, Leftover};
+use sdscanner::identity;
+use sdscanner::label;
+use sdscanner::planner::{self, Placement, Requirement};
+use sdscanner::preferences::{self, PreferenceChange, Preferences, ThemeChoice};
+use sdscanner::scanning::{self, Card, Game, Library, ScanData, ScanResult};
+use sdscanner::shortcuts::{self, Added, Candidate, Launch};
+use sdscanner::snapshot::{self, FileChanges};
+use sdscanner::steam::{self, LibraryEntry};
+use sdscanner::transfer::{self, Progress, Target, Transfer};
+use sdscanner::uninstall::{self, Removal};
+use sdscanner::verify::{self, VerifyReport};
+use sdscanner::Error;
 
-mod pages;
+mod card_watcher;
+mod gamepad;
+mod keyboard;
+pub mod pages;
 mod theming;
+mod transfer_progress;
 pub mod utils;
 
-use pages::Page;
-use utils::control_button;
+use gamepad::GamepadButton;
+use keyboard::{Key, Keyboard, KeyboardTarget};
+use pages::{ListView, Page, SortBy};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use utils::{control_button, FocusGrid, Focusables, ListOptions};
+
+/// How many items the triggers jump through the list at once
+const PAGE_SCROLL_ITEMS: usize = 10;
+/// How many games' art each background load handles, so the first covers show up quickly
+const ARTWORK_BATCH: usize = 12;
+/// How many problems are shown above the page at once, the rest are only in the error log
+const MAX_TOASTS: usize = 3;
+/// The error log drops its oldest entries past this
+const MAX_LOG_ENTRIES: usize = 200;
 
 pub struct App {
     pages: Vec<Page>,
     current: usize,
     card_data: Vec<Card>,
+    /// The cards mounted right now, looked up on scans and card watcher events rather than on every render
+    inserted_cards: Vec<ScanData>,
+    /// A scan is running in the background, the cards are scanned again once it's done if `rescan_requested` is set
+    scan_running: bool,
+    rescan_requested: bool,
     search_term: String,
+    /// Text pasted into the settings page from a scanned card label's QR code
+    label_import: String,
+    /// Feedback shown at the bottom of the settings page after a label action
+    settings_status: Option<String>,
+    list_view: ListView,
+    sort_by: SortBy,
+    /// UUIDs of the cards whose games are hidden in the list
+    collapsed_cards: HashSet<String>,
+    /// The grid's cover art that's been loaded, keyed by app ID
+    artwork: HashMap<u32, image::Handle>,
+    /// The app IDs whose art has been loaded or is loading, so each is only looked for once
+    artwork_requested: HashSet<u32>,
+    /// The game shown on the game page
+    selected_game: Option<GameId>,
+    /// The note being written on the game page, saved with Save Note
+    note_draft: String,
+    /// Feedback shown on the game page after an action
+    game_status: Option<String>,
+    /// What the last deep scans found changed in the selected game's folder
+    game_changes: Option<FileChanges>,
+    /// The UUID of the card shown on the card info page
+    selected_card: Option<String>,
+    /// The purchase note being written on the card info page, saved with Save Note
+    purchase_note_draft: String,
+    /// Feedback shown on the card info page after saving the note or running a benchmark
+    card_info_status: Option<String>,
+    /// The UUID of the card being benchmarked
+    benchmark_running: Option<String>,
+    /// The UUID of the card whose games are being verified
+    verify_running: Option<String>,
+    /// The libraries the selected game can be copied or moved to
+    transfer_targets: Vec<Target>,
+    /// The game being copied or moved, while it's running
+    transfer: Option<ActiveTransfer>,
+    /// The games picked on the uninstall page
+    uninstall: Option<UninstallSelection>,
+    /// The games picked on the add to Steam page
+    shortcut_selection: Option<ShortcutSelection>,
+    /// The size or app ID typed on the space planner page
+    planner_input: String,
+    /// The cards ranked for the last planned install, or why it couldn't be planned
+    planner_result: Option<Result<(Requirement, Vec<Placement>), Error>>,
+    /// The games Steam on this Deck knows about with where they're installed, or why they couldn't be read.
+    /// None while they're being loaded
+    steam_library: Option<Result<Vec<LibraryEntry>, Error>>,
+    /// Only list the games that are on cards that aren't inserted
+    other_cards_only: bool,
+    /// The button the controller is on, None until the controller is used
+    focus: Option<Focus>,
+    /// The on-screen keyboard, while it's open
+    keyboard: Option<Keyboard>,
+    preferences: Preferences,
+    /// The folder being typed into the settings page's extra library folder field
+    search_root_draft: String,
+    /// Whether the window is currently fullscreen
+    fullscreen: bool,
+    /// The card name being edited on the settings page, only applied to the card once it's committed
+    rename: Option<RenameDraft>,
+    /// The (UUID, old name) of every committed rename, newest last, used by Undo Rename
+    rename_history: Vec<(String, String)>,
+    /// A forget, archive or merge waiting for the user to confirm it
+    pending_card_action: Option<CardAction>,
+    /// The UUID of the card picked to be merged, while the card to merge it into is being picked
+    merge_from: Option<String>,
+    /// How to undo each confirmed card action with a description of it, newest last, used by Undo
+    card_history: Vec<(String, CardUndo)>,
+    /// Writes the card list after renames without holding up the UI
+    save_queue: scanning::SaveQueue,
+    /// Every problem reported while the app's been open, oldest first
+    error_log: Vec<LogEntry>,
+    /// The newest problems that haven't been dismissed, shown above the page
+    toasts: Vec<String>,
+}
+
+/// A problem in the error log
+pub struct LogEntry {
+    /// As a unix timestamp
+    pub time: u64,
+    pub message: String,
+}
+
+/// A card name that's being typed but hasn't been committed yet
+struct RenameDraft {
+    card_uuid: String,
+    name: String,
+}
+
+/// A copy or move started from the game page
+struct ActiveTransfer {
+    transfer: Transfer,
+    from_uuid: String,
+    target: Target,
+    progress: Option<Progress>,
+}
+
+/// The uninstall page's state for one card
+pub struct UninstallSelection {
+    pub card_uuid: String,
+    /// Where the card is mounted
+    pub card_path: PathBuf,
+    /// What uninstalling each game would remove, None while the sizes are still being added up
+    pub removals: Option<Vec<Removal>>,
+    /// Indexes into `removals`
+    pub selected: HashSet<usize>,
+    /// Folders on the card left behind by games that are gone, None while they're being looked for
+    pub leftovers: Option<Vec<Leftover>>,
+    /// Waiting for the user to confirm the uninstall
+    pub confirming: bool,
+    /// Waiting for the user to confirm removing the leftovers, apart from the Proton prefixes
+    pub confirming_leftovers: bool,
+    /// Waiting for the user to confirm removing the Proton prefixes, and the saves in them
+    pub confirming_prefixes: bool,
+    pub running: bool,
+    pub status: Option<String>,
+}
+
+/// The add to Steam page's state for one card
+pub struct ShortcutSelection {
+    pub card_uuid: String,
+    /// The card's Lutris and Heroic games, None while they're being looked through
+    pub candidates: Option<Vec<Candidate>>,
+    /// Indexes into `candidates`
+    pub selected: HashSet<usize>,
+    pub running: bool,
+    pub status: Option<String>,
+}
+
+impl UninstallSelection {
+    /// The selected games' removals
+    pub fn selected_removals(&self) -> Vec<Removal> {
+        self.removals
+            .iter()
+            .flatten()
+            .enumerate()
+            .filter(|(index, _)| self.selected.contains(index))
+            .map(|(_, removal)| removal.clone())
+            .collect()
+    }
+}
+
+/// The two parts of the window the controller focus moves between
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FocusZone {
+    /// The column of buttons on the left
+    Controls,
+    /// The current page
+    Content,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Focus {
+    zone: FocusZone,
+    /// Index into the zone's focusable buttons, in the order they're shown
+    index: usize,
+}
+
+/// Changes to a whole card entry made from the settings page, each one is confirmed first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CardAction {
+    /// Remove the card with the UUID from the list
+    Forget(String),
+    Archive(String),
+    Unarchive(String),
+    /// Merge the first card into the second, (old UUID, new UUID)
+    Merge(String, String),
+}
+
+/// Reverses one card action, only touching what the action changed so later edits are kept
+enum CardUndo {
+    /// Put a forgotten card back where it was in the list
+    Restore {
+        index: usize,
+        card: Box<Card>,
+    },
+    SetArchived {
+        card_uuid: String,
+        archived: bool,
+    },
+    /// Put the merged card back and take back what the merge copied onto `into`, which is the card from before the merge
+    Unmerge {
+        index: usize,
+        from: Box<Card>,
+        into: Box<Card>,
+    },
+}
+
+/// Identifies a single game in the saved list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameId {
+    pub card_uuid: String,
+    pub library: Library,
+    pub name: String,
 }
 
 impl Application for App {
     type Message = Message;
     type Executor = executor::Default;
     type Theme = Theme;
-    type Flags = ();
-
-    fn new(_flags: ()) -> (App, iced::Command<Message>) {
-        (
-            App {
-                pages: vec![Page::List, Page::Settings],
-                current: 0,
-                card_data: scanning::get_card_data(),
-                search_term: String::new(),
-            },
-            Command::none(),
-        )
+    /// The preferences, and anything that went wrong loading them
+    type Flags = (Preferences, Vec<Error>);
+
+    fn new((preferences, problems): Self::Flags) -> (App, iced::Command<Message>) {
+        let command = if preferences.fullscreen {
+            window::set_mode(window::Mode::Fullscreen)
+        } else {
+            Command::none()
+        };
+
+        let mut app = App {
+            pages: vec![
+                Page::List,
+                Page::Settings,
+                Page::Game,
+                Page::CardInfo,
+                Page::Uninstall,
+                Page::Planner,
+                Page::SteamLibrary,
+                Page::Shortcuts,
+                Page::ErrorLog,
+            ],
+            current: 0,
+            card_data: vec![],
+            inserted_cards: vec![],
+            scan_running: false,
+            rescan_requested: false,
+            search_term: String::new(),
+            label_import: String::new(),
+            settings_status: None,
+            list_view: preferences.default_view,
+            sort_by: preferences.default_sort,
+            collapsed_cards: HashSet::new(),
+            artwork: HashMap::new(),
+            artwork_requested: HashSet::new(),
+            selected_game: None,
+            note_draft: String::new(),
+            game_status: None,
+            game_changes: None,
+            selected_card: None,
+            purchase_note_draft: String::new(),
+            card_info_status: None,
+            benchmark_running: None,
+            verify_running: None,
+            transfer_targets: vec![],
+            transfer: None,
+            uninstall: None,
+            shortcut_selection: None,
+            planner_input: String::new(),
+            planner_result: None,
+            steam_library: None,
+            other_cards_only: false,
+            focus: None,
+            keyboard: None,
+            search_root_draft: String::new(),
+            fullscreen: preferences.fullscreen,
+            preferences,
+            rename: None,
+            rename_history: vec![],
+            pending_card_action: None,
+            merge_from: None,
+            card_history: vec![],
+            save_queue: scanning::SaveQueue::new(),
+            error_log: vec![],
+            toasts: vec![],
+        };
+        app.report(problems);
+        app.load_card_data();
+        let artwork = app.load_artwork();
+        let scan = app.start_scan();
+
+        (app, Command::batch([command, artwork, scan]))
     }
 
     fn title(&self) -> String {
-        format!("Steam Deck SD Card Scanner")
+        String::from("Steam Deck SD Card Scanner")
     }
 
     fn update(&mut self, event: Message) -> Command<Self::Message> {
+        // Saves happen in the background, anything that failed shows up with the next message
+        let save_errors = self.save_queue.take_errors();
+        self.report(save_errors);
+
         match event {
-            Message::ScanCard => crate::scanning::update_list(&mut self.card_data),
-            Message::Exit => std::process::exit(0),
-            Message::Fullscreen => return window::resize(1280, 800),
+            Message::ScanCard => return self.start_scan(),
+            Message::ScanFinished(result) => {
+                self.scan_running = false;
+                match result {
+                    Ok(result) => {
+                        let mut problems = result.warnings;
+                        scanning::add_scanned_cards(
+                            &mut self.card_data,
+                            result.cards,
+                            &mut problems,
+                        );
+                        self.inserted_cards = result.inserted;
+                        self.save_queue.save(&self.card_data);
+                        self.report(problems);
+                    }
+                    Err(e) => self.report([e]),
+                }
+
+                if std::mem::take(&mut self.rescan_requested) {
+                    return Command::batch([self.load_artwork(), self.start_scan()]);
+                }
+                return self.load_artwork();
+            }
+            Message::CardsChanged => self.refresh_inserted_cards(),
+            Message::DismissToast(index) => {
+                if index < self.toasts.len() {
+                    self.toasts.remove(index);
+                }
+            }
+            Message::ShowErrorLog => {
+                // They're all on the page
+                self.toasts.clear();
+                self.show_page(8);
+            }
+            Message::ClearErrorLog => self.error_log.clear(),
+            Message::Exit => {
+                self.commit_rename();
+                self.save_queue.flush();
+                std::process::exit(0)
+            }
+            Message::Fullscreen => {
+                self.fullscreen = !self.fullscreen;
+                if self.fullscreen {
+                    return window::set_mode(window::Mode::Fullscreen);
+                }
+
+                let size = self.preferences.window_size;
+                return Command::batch([
+                    window::set_mode(window::Mode::Windowed),
+                    window::resize(size.width, size.height),
+                ]);
+            }
             Message::SearchInput(text_input) => self.search_term = text_input,
-            Message::Settings => self.current = 1,
-            Message::Home => self.current = 0,
+            Message::Settings => {
+                self.show_page(1);
+                self.keyboard = None;
+            }
+            Message::Home => {
+                self.show_page(0);
+                self.keyboard = None;
+            }
             Message::ChangeCardName(card_name, card_uuid) => {
-                self.card_data = utils::change_card_name(card_name, card_uuid, &self.card_data)
+                // Starting to type into another card's name finishes the last one
+                if self
+                    .rename
+                    .as_ref()
+                    .is_some_and(|draft| draft.card_uuid != card_uuid)
+                {
+                    self.commit_rename();
+                }
+                self.rename = Some(RenameDraft {
+                    card_uuid,
+                    name: card_name,
+                });
+            }
+            Message::CommitRename => self.commit_rename(),
+            Message::CancelRename => {
+                self.rename = None;
+                if matches!(
+                    self.keyboard.as_ref().map(|keyboard| &keyboard.target),
+                    Some(KeyboardTarget::CardName(_))
+                ) {
+                    self.keyboard = None;
+                }
+            }
+            Message::StartMerge(card_uuid) => {
+                self.merge_from = Some(card_uuid);
+                self.pending_card_action = None;
+            }
+            Message::RequestCardAction(action) => {
+                self.merge_from = None;
+                self.pending_card_action = Some(action);
+            }
+            Message::CancelCardAction => {
+                self.merge_from = None;
+                self.pending_card_action = None;
+            }
+            Message::ConfirmCardAction => {
+                if let Some(action) = self.pending_card_action.take() {
+                    self.apply_card_action(action);
+                }
+            }
+            Message::UndoCardAction => {
+                if let Some((description, undo)) = self.card_history.pop() {
+                    self.undo_card_action(undo);
+                    self.save_queue.save(&self.card_data);
+                    self.settings_status = Some(format!("Undid: {description}"));
+                }
+            }
+            Message::UndoRename => {
+                if let Some((card_uuid, old_name)) = self.rename_history.pop() {
+                    if let Some(card) = self
+                        .card_data
+                        .iter_mut()
+                        .find(|card| card.uuid == card_uuid)
+                    {
+                        self.settings_status =
+                            Some(format!("Renamed {} back to {old_name}", card.name));
+                        card.name = old_name;
+                        self.save_queue.save(&self.card_data);
+                    }
+                }
+            }
+            Message::CreateLabel(card_uuid) => {
+                let saved = self
+                    .card_data
+                    .iter()
+                    .find(|card| card.uuid == card_uuid)
+                    .map(label::save_label);
+                self.settings_status = match saved {
+                    Some(Ok(path)) => Some(format!("Label saved to {}", path.display())),
+                    Some(Err(e)) => {
+                        self.report([e]);
+                        Some(String::from("Couldn't save the label"))
+                    }
+                    None => None,
+                };
+            }
+            Message::SetListView(list_view) => {
+                self.list_view = list_view;
+                return self.load_artwork();
+            }
+            Message::ArtworkLoaded(thumbnails, problems) => {
+                self.report(problems);
+                for (appid, thumbnail) in thumbnails {
+                    if let Some(thumbnail) = thumbnail {
+                        self.artwork.insert(
+                            appid,
+                            image::Handle::from_pixels(
+                                thumbnail.width,
+                                thumbnail.height,
+                                thumbnail.pixels,
+                            ),
+                        );
+                    }
+                }
+            }
+            Message::SetSortBy(sort_by) => self.sort_by = sort_by,
+            Message::ToggleCard(card_uuid) => {
+                if !self.collapsed_cards.remove(&card_uuid) {
+                    self.collapsed_cards.insert(card_uuid);
+                }
+            }
+            Message::ExpandAllCards => self.collapsed_cards.clear(),
+            Message::CollapseAllCards => {
+                self.collapsed_cards = self
+                    .card_data
+                    .iter()
+                    .map(|card| card.uuid.clone())
+                    .collect()
+            }
+            Message::ShowGame(game_id) => {
+                self.note_draft = utils::find_game(&self.card_data, &game_id)
+                    .map(|(_, game)| game.note.clone())
+                    .unwrap_or_default();
+                self.game_changes = match snapshot::game_changes(
+                    &game_id.card_uuid,
+                    game_id.library,
+                    &game_id.name,
+                ) {
+                    Ok(changes) => changes,
+                    Err(e) => {
+                        self.report([e]);
+                        None
+                    }
+                };
+                // Only Steam games can be moved, their appmanifest tells Steam where they are
+                self.transfer_targets = if game_id.library == Library::Steam {
+                    transfer::targets(&self.card_data, &game_id.card_uuid)
+                } else {
+                    vec![]
+                };
+                self.selected_game = Some(game_id);
+                self.game_status = None;
+                self.show_page(2);
+            }
+            Message::OpenGameFolder => {
+                if let Some(path) = self.selected_game_path().filter(|path| path.is_dir()) {
+                    self.game_status =
+                        match std::process::Command::new("xdg-open").arg(&path).spawn() {
+                            Ok(_) => None,
+                            Err(e) => Some(format!("Couldn't open the file manager: {e}")),
+                        };
+                }
+            }
+            Message::CopyGamePath => {
+                if let Some(path) = self.selected_game_path() {
+                    self.game_status = Some(String::from("Path copied"));
+                    return clipboard::write(path.display().to_string());
+                }
+            }
+            Message::ToggleFavourite => {
+                self.edit_selected_game(|game| game.favourite = !game.favourite);
+            }
+            Message::NoteInput(text_input) => self.note_draft = text_input,
+            Message::SaveNote => {
+                let note = self.note_draft.trim().to_string();
+                self.edit_selected_game(|game| game.note = note);
+                self.game_status = Some(String::from("Note saved"));
+            }
+            Message::StartTransfer(target_index, delete_source) => {
+                if self.transfer.is_some() {
+                    return Command::none();
+                }
+                let (game_id, target) =
+                    match (&self.selected_game, self.transfer_targets.get(target_index)) {
+                        (Some(game_id), Some(target)) => (game_id.clone(), target.clone()),
+                        _ => return Command::none(),
+                    };
+                let appid =
+                    utils::find_game(&self.card_data, &game_id).and_then(|(_, game)| game.appid);
+                let from = scanning::inserted_card_path(&self.card_data, &game_id.card_uuid);
+
+                self.game_status = match (appid, from) {
+                    _ if steam::running() => Some(String::from(
+                        "Close Steam first, moving games while it's running can corrupt its library",
+                    )),
+                    (None, _) => Some(String::from("Steam's appmanifest for this game wasn't found")),
+                    (_, None) => Some(String::from("Insert the card the game is on first")),
+                    (Some(appid), Some(from)) => {
+                        self.transfer = Some(ActiveTransfer {
+                            transfer: Transfer {
+                                game: game_id.name.clone(),
+                                appid,
+                                from,
+                                to: target.path.clone(),
+                                delete_source,
+                            },
+                            from_uuid: game_id.card_uuid.clone(),
+                            target,
+                            progress: None,
+                        });
+                        None
+                    }
+                };
+            }
+            Message::TransferProgress(progress) => {
+                if let Some(active) = &mut self.transfer {
+                    active.progress = Some(progress);
+                }
+            }
+            Message::TransferFinished(result) => {
+                let active = match self.transfer.take() {
+                    Some(active) => active,
+                    None => return Command::none(),
+                };
+                let moved = active.transfer.delete_source;
+
+                self.game_status = Some(match result {
+                    Ok(()) => {
+                        transfer::record(
+                            &mut self.card_data,
+                            &active.transfer,
+                            Some(&active.from_uuid),
+                            active.target.card_uuid.as_deref(),
+                        );
+                        self.save_queue.save(&self.card_data);
+
+                        // Follow the game to the card it moved to
+                        if let (true, Some(game_id), Some(to_uuid)) =
+                            (moved, &mut self.selected_game, &active.target.card_uuid)
+                        {
+                            game_id.card_uuid = to_uuid.clone();
+                        }
+                        format!(
+                            "{} to {}",
+                            if moved { "Moved" } else { "Copied" },
+                            active.target.name
+                        )
+                    }
+                    Err(problem) => problem.to_string(),
+                });
+            }
+            Message::ShowPlanner => self.show_page(5),
+            Message::ShowSteamLibrary => {
+                self.steam_library = None;
+                self.show_page(6);
+
+                // Reading appinfo.vdf can take a while, it's done away from the UI
+                let list = self.card_data.clone();
+                let inserted = self.connected_cards().into_keys().collect();
+                return Command::perform(
+                    async move {
+                        let mut problems = vec![];
+                        let library = steam::library(&list, &inserted, &mut problems);
+                        (library, problems)
+                    },
+                    |(library, problems)| Message::SteamLibraryLoaded(library, problems),
+                );
             }
+            Message::SteamLibraryLoaded(library, problems) => {
+                self.steam_library = Some(library);
+                self.report(problems);
+            }
+            Message::ToggleOtherCardsOnly => self.other_cards_only = !self.other_cards_only,
+            Message::PlannerInput(text_input) => self.planner_input = text_input,
+            Message::RunPlanner => {
+                let requirement = planner::parse_requirement(&self.planner_input, &self.card_data);
+                self.planner_result = Some(requirement.map(|requirement| {
+                    let placements =
+                        planner::plan(&self.card_data, requirement.bytes, &self.connected_cards());
+                    (requirement, placements)
+                }));
+            }
+            Message::PlanGame(game_id) => {
+                let requirement = utils::find_game(&self.card_data, &game_id)
+                    .ok_or_else(|| {
+                        Error::Invalid(String::from("This game is no longer on the list"))
+                    })
+                    .and_then(|(_, game)| planner::game_requirement(game));
+                self.planner_input = match &requirement {
+                    Ok(requirement) => scanning::format_size(requirement.bytes),
+                    Err(_) => String::new(),
+                };
+                self.planner_result = Some(requirement.map(|requirement| {
+                    let placements =
+                        planner::plan(&self.card_data, requirement.bytes, &self.connected_cards());
+                    (requirement, placements)
+                }));
+                self.show_page(5);
+            }
+            Message::ShowCardInfo(card_uuid) => {
+                self.purchase_note_draft = self
+                    .card_data
+                    .iter()
+                    .find(|card| card.uuid == card_uuid)
+                    .map(|card| card.purchase_note.clone())
+                    .unwrap_or_default();
+                self.selected_card = Some(card_uuid);
+                self.card_info_status = None;
+                self.show_page(3);
+            }
+            Message::ShowUninstall(card_uuid) => {
+                let card = self.card_data.iter().find(|card| card.uuid == card_uuid);
+                let mount_path = scanning::inserted_card_path(&self.card_data, &card_uuid);
+
+                let card = match (card, mount_path) {
+                    (Some(card), Some(mount_path)) => Card {
+                        mount_path,
+                        ..card.clone()
+                    },
+                    _ => {
+                        self.card_info_status =
+                            Some(String::from("Insert the card to uninstall its games"));
+                        return Command::none();
+                    }
+                };
+
+                self.uninstall = Some(UninstallSelection {
+                    card_uuid: card_uuid.clone(),
+                    card_path: card.mount_path.clone(),
+                    removals: None,
+                    selected: HashSet::new(),
+                    leftovers: None,
+                    confirming: false,
+                    confirming_leftovers: false,
+                    confirming_prefixes: false,
+                    running: false,
+                    status: None,
+                });
+                self.show_page(4);
+                let card_path = card.mount_path.clone();
+                let leftovers_uuid = card_uuid.clone();
+                return Command::batch([
+                    Command::perform(async move { uninstall::plan(&card) }, |removals| {
+                        Message::UninstallPlanReady(card_uuid, removals)
+                    }),
+                    Command::perform(
+                        async move { cleanup::find_leftovers(&card_path) },
+                        |leftovers| Message::LeftoversFound(leftovers_uuid, leftovers),
+                    ),
+                ]);
+            }
+            Message::ShowShortcuts(card_uuid) => {
+                let card = self.card_data.iter().find(|card| card.uuid == card_uuid);
+                let mount_path = scanning::inserted_card_path(&self.card_data, &card_uuid);
+
+                let card = match (card, mount_path) {
+                    (Some(card), Some(mount_path)) => Card {
+                        mount_path,
+                        ..card.clone()
+                    },
+                    _ => {
+                        self.card_info_status =
+                            Some(String::from("Insert the card to add its games to Steam"));
+                        return Command::none();
+                    }
+                };
+
+                self.shortcut_selection = Some(ShortcutSelection {
+                    card_uuid: card_uuid.clone(),
+                    candidates: None,
+                    selected: HashSet::new(),
+                    running: false,
+                    status: None,
+                });
+                self.show_page(7);
+                return Command::perform(
+                    async move { shortcuts::candidates(&card) },
+                    |candidates| Message::ShortcutCandidatesFound(card_uuid, candidates),
+                );
+            }
+            Message::ShortcutCandidatesFound(card_uuid, candidates) => {
+                if let Some(selection) = &mut self.shortcut_selection {
+                    if selection.card_uuid == card_uuid {
+                        match candidates {
+                            Ok(candidates) => selection.candidates = Some(candidates),
+                            Err(problem) => {
+                                selection.candidates = Some(vec![]);
+                                selection.status = Some(problem.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+            Message::ToggleShortcutGame(index) => {
+                if let Some(selection) = &mut self.shortcut_selection {
+                    if !selection.selected.remove(&index) {
+                        selection.selected.insert(index);
+                    }
+                }
+            }
+            Message::ToggleShortcutLaunch(index) => {
+                if let Some(candidate) = self
+                    .shortcut_selection
+                    .as_mut()
+                    .and_then(|selection| selection.candidates.as_mut())
+                    .and_then(|candidates| candidates.get_mut(index))
+                {
+                    candidate.launch = match (&candidate.launch, &candidate.executable) {
+                        (Launch::Launcher, Some(executable)) => {
+                            Launch::Executable(executable.clone())
+                        }
+                        _ => Launch::Launcher,
+                    };
+                }
+            }
+            Message::AddShortcuts => {
+                let selection = match &mut self.shortcut_selection {
+                    Some(selection) if !selection.running => selection,
+                    _ => return Command::none(),
+                };
+                let picked: Vec<shortcuts::Shortcut> = selection
+                    .candidates
+                    .iter()
+                    .flatten()
+                    .enumerate()
+                    .filter(|(index, _)| selection.selected.contains(index))
+                    .map(|(_, candidate)| shortcuts::shortcut(candidate))
+                    .collect();
+                if picked.is_empty() {
+                    return Command::none();
+                }
+                selection.running = true;
+                selection.status = Some(String::from("Adding shortcuts..."));
+
+                return Command::perform(
+                    async move { shortcuts::add(&picked) },
+                    Message::ShortcutsAdded,
+                );
+            }
+            Message::ShortcutsAdded(result) => {
+                if let Some(selection) = &mut self.shortcut_selection {
+                    selection.running = false;
+                    selection.status = Some(match result {
+                        Ok(added) => {
+                            // They're in Steam now, so they can't be added again
+                            for candidate in selection.candidates.iter_mut().flatten() {
+                                if added.added.contains(&candidate.game) {
+                                    candidate.in_steam = true;
+                                }
+                            }
+                            selection.selected.clear();
+                            added.summary()
+                        }
+                        Err(problem) => problem.to_string(),
+                    });
+                }
+            }
+            Message::UninstallPlanReady(card_uuid, removals) => {
+                if let Some(selection) = &mut self.uninstall {
+                    if selection.card_uuid == card_uuid {
+                        selection.removals = Some(removals);
+                    }
+                }
+            }
+            Message::ToggleUninstallGame(index) => {
+                if let Some(selection) = &mut self.uninstall {
+                    if !selection.selected.remove(&index) {
+                        selection.selected.insert(index);
+                    }
+                    selection.confirming = false;
+                }
+            }
+            Message::RequestUninstall => {
+                if let Some(selection) = &mut self.uninstall {
+                    selection.confirming = !selection.selected.is_empty();
+                }
+            }
+            Message::CancelUninstall => {
+                if let Some(selection) = &mut self.uninstall {
+                    selection.confirming = false;
+                    selection.confirming_leftovers = false;
+                    selection.confirming_prefixes = false;
+                }
+            }
+            Message::LeftoversFound(card_uuid, leftovers) => {
+                if let Some(selection) = &mut self.uninstall {
+                    if selection.card_uuid == card_uuid {
+                        selection.leftovers = Some(leftovers);
+                    }
+                }
+            }
+            Message::RequestRemoveLeftovers(prefixes) => {
+                if let Some(selection) = &mut self.uninstall {
+                    let any = selection.leftovers.as_ref().is_some_and(|leftovers| {
+                        leftovers
+                            .iter()
+                            .any(|leftover| leftover.kind.holds_saves() == prefixes)
+                    });
+                    if prefixes {
+                        selection.confirming_prefixes = any;
+                    } else {
+                        selection.confirming_leftovers = any;
+                    }
+                }
+            }
+            Message::ConfirmRemoveLeftovers(prefixes) => {
+                let selection = match &mut self.uninstall {
+                    Some(selection) if !selection.running => selection,
+                    _ => return Command::none(),
+                };
+                // Proton prefixes are only removed when they were confirmed on their own
+                let leftovers: Vec<Leftover> = selection
+                    .leftovers
+                    .take()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|leftover| leftover.kind.holds_saves() == prefixes)
+                    .collect();
+                let card_uuid = selection.card_uuid.clone();
+                let card_path = selection.card_path.clone();
+                selection.confirming_leftovers = false;
+                selection.confirming_prefixes = false;
+                selection.running = true;
+                selection.status = Some(String::from("Removing leftover folders..."));
+
+                return Command::perform(
+                    async move {
+                        let freed = leftovers.iter().map(|leftover| leftover.bytes).sum();
+                        let result = cleanup::remove(&leftovers).map(|()| freed);
+                        // Looked for again so the list shows whatever couldn't be removed
+                        (result, cleanup::find_leftovers(&card_path))
+                    },
+                    move |(result, remaining)| {
+                        Message::LeftoversRemoved(card_uuid.clone(), result, remaining)
+                    },
+                );
+            }
+            Message::LeftoversRemoved(card_uuid, result, remaining) => {
+                if let Some(selection) = &mut self.uninstall {
+                    if selection.card_uuid == card_uuid {
+                        selection.leftovers = Some(remaining);
+                        selection.running = false;
+                        selection.status = Some(match result {
+                            Ok(freed) => format!("Freed {}", scanning::format_size(freed)),
+                            Err(problem) => problem.to_string(),
+                        });
+                    }
+                }
+            }
+            Message::ConfirmUninstall => {
+                let selection = match &mut self.uninstall {
+                    Some(selection) if !selection.running => selection,
+                    _ => return Command::none(),
+                };
+                let removals = selection.selected_removals();
+                let card_uuid = selection.card_uuid.clone();
+                selection.confirming = false;
+                selection.running = true;
+                selection.status = Some(String::from("Uninstalling..."));
+
+                return Command::perform(
+                    async move {
+                        let result = uninstall::run(&removals);
+                        (removals, result)
+                    },
+                    move |(removals, result)| {
+                        Message::UninstallFinished(card_uuid.clone(), removals, result)
+                    },
+                );
+            }
+            Message::UninstallFinished(card_uuid, removals, result) => {
+                let mut find_leftovers = Command::none();
+                if let Some(card) = self
+                    .card_data
+                    .iter_mut()
+                    .find(|card| card.uuid == card_uuid)
+                {
+                    uninstall::record(card, &removals);
+                    self.save_queue.save(&self.card_data);
+                }
+
+                let freed: u64 = removals
+                    .iter()
+                    .filter(|removal| removal.paths.iter().all(|path| !path.exists()))
+                    .map(|removal| removal.bytes)
+                    .sum();
+                if let Some(selection) = &mut self.uninstall {
+                    // The remaining games keep their sizes, the list just loses the removed ones
+                    if let Some(all) = &mut selection.removals {
+                        all.retain(|removal| removal.paths.iter().any(|path| path.exists()));
+                    }
+                    selection.selected.clear();
+                    selection.running = false;
+                    selection.status = Some(match result {
+                        Ok(()) => format!("Freed {}", scanning::format_size(freed)),
+                        Err(problem) => problem.to_string(),
+                    });
+
+                    // The uninstalled games' Proton prefixes are leftovers now
+                    selection.leftovers = None;
+                    let card_path = selection.card_path.clone();
+                    find_leftovers = Command::perform(
+                        async move { cleanup::find_leftovers(&card_path) },
+                        move |leftovers| Message::LeftoversFound(card_uuid.clone(), leftovers),
+                    );
+                }
+                return find_leftovers;
+            }
+            Message::PurchaseNoteInput(text_input) => self.purchase_note_draft = text_input,
+            Message::SavePurchaseNote => {
+                let selected_card = self.selected_card.as_ref();
+                if let Some(card) = self
+                    .card_data
+                    .iter_mut()
+                    .find(|card| Some(&card.uuid) == selected_card)
+                {
+                    card.purchase_note = self.purchase_note_draft.trim().to_string();
+                    self.save_queue.save(&self.card_data);
+                    self.card_info_status = Some(String::from("Note saved"));
+                }
+            }
+            Message::RunBenchmark(card_uuid, write_test) => {
+                if self.benchmark_running.is_some() {
+                    return Command::none();
+                }
+                let card = self.card_data.iter().find(|card| card.uuid == card_uuid);
+                let mount_path = scanning::inserted_card_path(&self.card_data, &card_uuid);
+
+                let card = match (card, mount_path) {
+                    (Some(card), Some(mount_path)) => Card {
+                        mount_path,
+                        ..card.clone()
+                    },
+                    _ => {
+                        self.card_info_status =
+                            Some(String::from("Insert the card to test its speed"));
+                        return Command::none();
+                    }
+                };
+
+                self.benchmark_running = Some(card_uuid.clone());
+                self.card_info_status = Some(String::from("Testing, this takes a minute..."));
+                return Command::perform(
+                    async move { benchmark::run(&card, write_test) },
+                    |result| Message::BenchmarkFinished(card_uuid, result),
+                );
+            }
+            Message::BenchmarkFinished(card_uuid, result) => {
+                self.benchmark_running = None;
+                match result {
+                    Ok(result) => {
+                        if let Some(card) = self
+                            .card_data
+                            .iter_mut()
+                            .find(|card| card.uuid == card_uuid)
+                        {
+                            self.card_info_status = Some(result.summary());
+                            card.add_benchmark(result);
+                            self.save_queue.save(&self.card_data);
+                        }
+                    }
+                    Err(problem) => {
+                        self.card_info_status = Some(problem.to_string());
+                        self.report([problem]);
+                    }
+                }
+            }
+            Message::VerifyCard(card_uuid) => {
+                if self.verify_running.is_some() {
+                    return Command::none();
+                }
+                let card = self.card_data.iter().find(|card| card.uuid == card_uuid);
+                let mount_path = scanning::inserted_card_path(&self.card_data, &card_uuid);
+
+                let card = match (card, mount_path) {
+                    (Some(card), Some(mount_path)) => Card {
+                        mount_path,
+                        ..card.clone()
+                    },
+                    _ => {
+                        self.card_info_status =
+                            Some(String::from("Insert the card to verify its games"));
+                        return Command::none();
+                    }
+                };
+
+                self.verify_running = Some(card_uuid.clone());
+                self.card_info_status = Some(String::from("Verifying, this can take a while..."));
+                return Command::perform(async move { verify::verify_card(&card) }, |report| {
+                    Message::VerifyFinished(card_uuid, report)
+                });
+            }
+            Message::VerifyFinished(card_uuid, report) => {
+                self.verify_running = None;
+                self.report(report.warnings.iter().cloned());
+                if let Some(card) = self
+                    .card_data
+                    .iter_mut()
+                    .find(|card| card.uuid == card_uuid)
+                {
+                    for (library, name, problems) in &report.games {
+                        if let Some(game) = card
+                            .library_games_mut(*library)
+                            .and_then(|games| games.iter_mut().find(|game| &game.name == name))
+                        {
+                            game.problems = problems.clone();
+                        }
+                    }
+                    self.save_queue.save(&self.card_data);
+                }
+
+                let suspect = report.suspect_count();
+                let mut status = match suspect {
+                    0 => format!("All {} games look fine", report.games.len()),
+                    1 => String::from("1 game has problems"),
+                    _ => format!("{suspect} games have problems"),
+                };
+                if let Some(problem) = report.filesystem_problem {
+                    status = format!("{problem}. {status}");
+                }
+                self.card_info_status = Some(status);
+            }
+            Message::Gamepad(button) => return self.gamepad_input(button),
+            Message::OpenKeyboard(target) => {
+                // Tapping the field again while its keyboard is open leaves the keyboard as it is
+                if self.keyboard.as_ref().map(|keyboard| &keyboard.target) != Some(&target) {
+                    self.keyboard = Some(Keyboard::new(target));
+                }
+            }
+            Message::KeyboardKey(Key::Done) => {
+                if let Some(Keyboard {
+                    target: KeyboardTarget::CardName(_),
+                    ..
+                }) = self.keyboard
+                {
+                    self.commit_rename();
+                }
+                let plan = matches!(
+                    self.keyboard,
+                    Some(Keyboard {
+                        target: KeyboardTarget::PlannerSize,
+                        ..
+                    })
+                );
+                self.keyboard = None;
+                if plan {
+                    return self.update(Message::RunPlanner);
+                }
+            }
+            Message::KeyboardKey(key) => {
+                let target = match &self.keyboard {
+                    Some(keyboard) => keyboard.target.clone(),
+                    None => return Command::none(),
+                };
+                let typed = self.keyboard_text(&target);
+
+                // Send the new text the same way typing into the field would
+                if let Some(typed) = self.keyboard.as_mut().and_then(|k| k.press(key, &typed)) {
+                    return self.update(match target {
+                        KeyboardTarget::Search => Message::SearchInput(typed),
+                        KeyboardTarget::CardName(card_uuid) => {
+                            Message::ChangeCardName(typed, card_uuid)
+                        }
+                        KeyboardTarget::LabelImport => Message::LabelImportInput(typed),
+                        KeyboardTarget::Note => Message::NoteInput(typed),
+                        KeyboardTarget::PurchaseNote => Message::PurchaseNoteInput(typed),
+                        KeyboardTarget::SearchRoot => Message::SearchRootInput(typed),
+                        KeyboardTarget::PlannerSize => Message::PlannerInput(typed),
+                    });
+                }
+            }
+            Message::LabelImportInput(text_input) => self.label_import = text_input,
+            Message::ImportLabel => {
+                self.settings_status =
+                    match label::import_payload(&self.label_import, &mut self.card_data) {
+                        Ok(card_name) => {
+                            self.save_queue.save(&self.card_data);
+                            self.label_import.clear();
+                            Some(format!("Imported card {card_name}"))
+                        }
+                        Err(e) => Some(e.to_string()),
+                    };
+            }
+            Message::ChangePreference(change) => return self.change_preference(change),
+            Message::SearchRootInput(text_input) => self.search_root_draft = text_input,
         }
 
         Command::none()
     }
 
-    fn view(&self) -> Element<Message> {
+    fn view(&self) -> Element<'_, Message> {
         let mut controls = vec![container(text("Steam Deck\nSD Card Scanner").size(50))
             .padding(10)
             .into()];
 
-        if self.current != 0 {
-            controls.push(control_button("Home", Message::Home));
-        }
-        if self.current != 1 {
-            controls.push(control_button("Settings", Message::Settings));
+        let mut control_focus = Focusables::new(self.focused_index(FocusZone::Controls));
+        for (label, message) in self.control_buttons() {
+            controls.push(control_button(&mut control_focus, label, message));
         }
-        controls.push(control_button("Rescan Card", Message::ScanCard));
-        controls.push(control_button("Exit", Message::Exit));
-        controls.push(control_button("Fullscreen", Message::Fullscreen));
 
         let controls_column = column(controls).padding(12).align_items(Alignment::Center);
 
-        let content = self.pages[self.current].view(&self.card_data, &self.search_term);
+        let mut content_focus = Focusables::new(self.focused_index(FocusZone::Content));
+        let mut content = self.content(&mut content_focus);
+
+        if let Some(keyboard) = &self.keyboard {
+            let label = match &keyboard.target {
+                KeyboardTarget::Search => "Search",
+                KeyboardTarget::CardName(_) => "Card Name",
+                KeyboardTarget::LabelImport => "Label Data",
+                KeyboardTarget::Note => "Note",
+                KeyboardTarget::PurchaseNote => "Purchase Note",
+                KeyboardTarget::SearchRoot => "Library Folder",
+                KeyboardTarget::PlannerSize => "Game Size",
+            };
+            content = column![
+                container(content).height(Length::Fill),
+                keyboard.view(label, &self.keyboard_text(&keyboard.target)),
+            ]
+            .into();
+        }
 
         container(row!(controls_column, content))
             .width(Length::Fill)
@@ -79,22 +1206,760 @@ impl Application for App {
             .into()
     }
 
+    fn subscription(&self) -> Subscription<Message> {
+        let mut subscriptions = vec![
+            gamepad::subscription(),
+            // Closing the window goes through Exit so the rename and the queued save aren't lost
+            subscription::events_with(|event, _| match event {
+                Event::Window(window::Event::CloseRequested) => Some(Message::Exit),
+                _ => None,
+            }),
+        ];
+
+        if self.preferences.auto_rescan {
+            subscriptions.push(card_watcher::subscription());
+        }
+        if let Some(active) = &self.transfer {
+            subscriptions.push(transfer_progress::subscription(active.transfer.clone()));
+        }
+        if self.rename.is_some() {
+            // Escape cancels the rename
+            subscriptions.push(subscription::events_with(|event, _| match event {
+                Event::Keyboard(iced::keyboard::Event::KeyPressed {
+                    key_code: iced::keyboard::KeyCode::Escape,
+                    ..
+                }) => Some(Message::CancelRename),
+                _ => None,
+            }));
+        }
+
+        Subscription::batch(subscriptions)
+    }
+
     fn theme(&self) -> Theme {
-        Theme::Dark
+        match self.preferences.theme {
+            ThemeChoice::Dark => Theme::Dark,
+            ThemeChoice::Light => Theme::Light,
+        }
+    }
+
+    fn scale_factor(&self) -> f64 {
+        self.preferences.font_scale as f64 / 100.0
+    }
+}
+
+impl App {
+    /// The buttons in the column on the left, depending on the current page
+    fn control_buttons(&self) -> Vec<(&'static str, Message)> {
+        let mut buttons = vec![];
+
+        if self.current != 0 {
+            buttons.push(("Home", Message::Home));
+        }
+        if self.current != 1 {
+            buttons.push(("Settings", Message::Settings));
+        }
+        if self.current != 5 {
+            buttons.push(("Space Planner", Message::ShowPlanner));
+        }
+        if self.current != 6 {
+            buttons.push(("Steam Library", Message::ShowSteamLibrary));
+        }
+        if self.current != 8 && !self.error_log.is_empty() {
+            buttons.push(("Error Log", Message::ShowErrorLog));
+        }
+        buttons.push(("Rescan Card", Message::ScanCard));
+        buttons.push(("Exit", Message::Exit));
+        buttons.push((
+            if self.fullscreen {
+                "Windowed"
+            } else {
+                "Fullscreen"
+            },
+            Message::Fullscreen,
+        ));
+
+        buttons
+    }
+
+    /// (UUID, mount path) of the saved cards that are inserted right now
+    fn connected_cards(&self) -> HashMap<String, PathBuf> {
+        self.inserted_cards
+            .iter()
+            .filter_map(|scanned| {
+                let index = identity::find_card(&self.card_data, scanned)?;
+                Some((
+                    self.card_data[index].uuid.clone(),
+                    scanned.card_path.clone(),
+                ))
+            })
+            .collect()
+    }
+
+    fn list_options(&self) -> ListOptions<'_> {
+        ListOptions {
+            search_term: &self.search_term,
+            list_view: self.list_view,
+            sort_by: self.sort_by,
+            collapsed_cards: &self.collapsed_cards,
+            artwork: &self.artwork,
+        }
+    }
+
+    /// Starts loading the cover art of the Steam games that don't have it yet, only while the grid is shown
+    fn load_artwork(&mut self) -> Command<Message> {
+        if self.list_view != ListView::Grid {
+            return Command::none();
+        }
+
+        let mut appids = vec![];
+        for card in &self.card_data {
+            for game in &card.games {
+                if let Some(appid) = game.appid {
+                    if self.artwork_requested.insert(appid) {
+                        appids.push(appid);
+                    }
+                }
+            }
+        }
+
+        Command::batch(appids.chunks(ARTWORK_BATCH).map(|appids| {
+            let appids = appids.to_vec();
+            Command::perform(
+                async move {
+                    let mut problems = vec![];
+                    let thumbnails = appids
+                        .into_iter()
+                        .map(|appid| (appid, artwork::load_thumbnail(appid, &mut problems)))
+                        .collect();
+                    (thumbnails, problems)
+                },
+                |(thumbnails, problems)| Message::ArtworkLoaded(thumbnails, problems),
+            )
+        }))
+    }
+
+    fn focused_index(&self, zone: FocusZone) -> Option<usize> {
+        self.focus
+            .filter(|focus| focus.zone == zone)
+            .map(|focus| focus.index)
+    }
+
+    /// The focusable buttons on the current page, this builds the whole page so it's only done once per button press
+    fn content_focusables(&self) -> Focusables {
+        let mut focus = Focusables::new(None);
+        self.content(&mut focus);
+        focus
+    }
+
+    /// The current page with the toasts above it, their Dismiss buttons come first for the controller
+    fn content(&self, focus: &mut Focusables) -> Element<'_, Message> {
+        if self.toasts.is_empty() {
+            return self.pages[self.current].view(self, focus);
+        }
+
+        let mut toasts: Vec<Element<Message>> = vec![];
+        for (index, message) in self.toasts.iter().enumerate() {
+            toasts.push(
+                container(
+                    row![
+                        text(message)
+                            .size(20)
+                            .style(theming::ERROR_COLOR)
+                            .width(Length::Fill),
+                        focus.button(text("Dismiss"), Message::DismissToast(index)),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                )
+                .style(theming::TOAST_CONTAINER_STYLE)
+                .padding(8)
+                .width(Length::Fill)
+                .into(),
+            );
+        }
+
+        column![
+            column(toasts).spacing(4).padding(4),
+            self.pages[self.current].view(self, focus),
+        ]
+        .into()
+    }
+
+    /// Adds the problems to the error log and shows the newest ones above the page
+    fn report(&mut self, problems: impl IntoIterator<Item = Error>) {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+
+        for problem in problems {
+            let message = problem.to_string();
+            self.toasts.push(message.clone());
+            self.error_log.push(LogEntry { time, message });
+        }
+
+        if self.toasts.len() > MAX_TOASTS {
+            self.toasts.drain(..self.toasts.len() - MAX_TOASTS);
+        }
+        if self.error_log.len() > MAX_LOG_ENTRIES {
+            self.error_log
+                .drain(..self.error_log.len() - MAX_LOG_ENTRIES);
+        }
+    }
+
+    /// Fills the list from the save file, an unreadable save file starts an empty list.
+    /// Saved right away so the file is there even if nothing else changes, the inserted cards are scanned in after
+    fn load_card_data(&mut self) {
+        self.card_data = match scanning::load_cards() {
+            Ok(list) => list,
+            Err(e) => {
+                self.report([e]);
+                vec![]
+            }
+        };
+        if let Err(e) = scanning::save_cards(&self.card_data) {
+            self.report([e]);
+        }
+        self.refresh_inserted_cards();
+    }
+
+    /// Scans the inserted cards in the background, a deep scan can read every file on the card.
+    /// A scan asked for while one is running happens after it, so a card inserted meanwhile isn't missed
+    fn start_scan(&mut self) -> Command<Message> {
+        if self.scan_running {
+            self.rescan_requested = true;
+            return Command::none();
+        }
+        self.scan_running = true;
+
+        let list = self.card_data.clone();
+        let preferences = self.preferences.clone();
+        Command::perform(
+            async move { scanning::scan_inserted_cards(&list, &preferences) },
+            Message::ScanFinished,
+        )
+    }
+
+    /// Looks up which cards are mounted, the list page and planner read the result instead of running lsblk themselves
+    fn refresh_inserted_cards(&mut self) {
+        self.inserted_cards = scanning::discover_cards().unwrap_or_default();
+    }
+
+    /// The current text of the field the on-screen keyboard is typing into
+    fn keyboard_text(&self, target: &KeyboardTarget) -> String {
+        match target {
+            KeyboardTarget::Search => self.search_term.clone(),
+            KeyboardTarget::CardName(card_uuid) => self.card_name_text(card_uuid),
+            KeyboardTarget::LabelImport => self.label_import.clone(),
+            KeyboardTarget::Note => self.note_draft.clone(),
+            KeyboardTarget::PurchaseNote => self.purchase_note_draft.clone(),
+            KeyboardTarget::SearchRoot => self.search_root_draft.clone(),
+            KeyboardTarget::PlannerSize => self.planner_input.clone(),
+        }
+    }
+
+    /// The text shown in a card's name field, the draft while it's being renamed
+    fn card_name_text(&self, card_uuid: &str) -> String {
+        match &self.rename {
+            Some(draft) if draft.card_uuid == card_uuid => draft.name.clone(),
+            _ => self
+                .card_data
+                .iter()
+                .find(|card| card.uuid == card_uuid)
+                .map(|card| card.name.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn card_name(&self, card_uuid: &str) -> String {
+        self.card_data
+            .iter()
+            .find(|card| card.uuid == card_uuid)
+            .map_or_else(|| String::from("Unknown card"), |card| card.name.clone())
+    }
+
+    /// The question asked before a card action is applied
+    fn card_action_prompt(&self, action: &CardAction) -> String {
+        match action {
+            CardAction::Forget(card_uuid) => format!(
+                "Forget {}? Its saved game list, favourites and notes will be removed",
+                self.card_name(card_uuid)
+            ),
+            CardAction::Archive(card_uuid) => format!(
+                "Archive {}? It will only show up in the list when searching",
+                self.card_name(card_uuid)
+            ),
+            CardAction::Unarchive(card_uuid) => {
+                format!("Show {} in the list again?", self.card_name(card_uuid))
+            }
+            CardAction::Merge(from_uuid, into_uuid) => format!(
+                "Merge {} into {}? The old entry's name, favourites and notes move over and the old entry is removed",
+                self.card_name(from_uuid),
+                self.card_name(into_uuid)
+            ),
+        }
+    }
+
+    /// Applies a confirmed card action, keeping what's needed to undo it
+    fn apply_card_action(&mut self, action: CardAction) {
+        let position =
+            |list: &[Card], card_uuid: &str| list.iter().position(|card| card.uuid == card_uuid);
+
+        let (description, undo) = match &action {
+            CardAction::Forget(card_uuid) => {
+                let index = match position(&self.card_data, card_uuid) {
+                    Some(index) => index,
+                    None => return,
+                };
+                let card = self.card_data.remove(index);
+                (
+                    format!("Forgot {}", card.name),
+                    CardUndo::Restore {
+                        index,
+                        card: Box::new(card),
+                    },
+                )
+            }
+            CardAction::Archive(card_uuid) | CardAction::Unarchive(card_uuid) => {
+                let archived = matches!(action, CardAction::Archive(_));
+                let card = match self
+                    .card_data
+                    .iter_mut()
+                    .find(|card| &card.uuid == card_uuid)
+                {
+                    Some(card) => card,
+                    None => return,
+                };
+                let undo = CardUndo::SetArchived {
+                    card_uuid: card_uuid.clone(),
+                    archived: card.archived,
+                };
+                card.archived = archived;
+                let description = if archived {
+                    format!("Archived {}", card.name)
+                } else {
+                    format!("Unarchived {}", card.name)
+                };
+                (description, undo)
+            }
+            CardAction::Merge(from_uuid, into_uuid) => {
+                let (index, from, into) = match (
+                    position(&self.card_data, from_uuid),
+                    position(&self.card_data, into_uuid),
+                ) {
+                    (Some(index), Some(into_index)) => (
+                        index,
+                        Box::new(self.card_data[index].clone()),
+                        Box::new(self.card_data[into_index].clone()),
+                    ),
+                    _ => return,
+                };
+                if !scanning::merge_cards(&mut self.card_data, from_uuid, into_uuid) {
+                    return;
+                }
+                (
+                    format!("Merged {} into {}", from.name, into.name),
+                    CardUndo::Unmerge { index, from, into },
+                )
+            }
+        };
+
+        self.save_queue.save(&self.card_data);
+        self.settings_status = Some(description.clone());
+        self.card_history.push((description, undo));
+    }
+
+    /// Reverses a card action, anything else changed since it was applied stays as it is
+    fn undo_card_action(&mut self, undo: CardUndo) {
+        match undo {
+            CardUndo::Restore { index, card } => self.reinsert_card(index, *card),
+            CardUndo::SetArchived {
+                card_uuid,
+                archived,
+            } => {
+                if let Some(card) = self
+                    .card_data
+                    .iter_mut()
+                    .find(|card| card.uuid == card_uuid)
+                {
+                    card.archived = archived;
+                }
+            }
+            CardUndo::Unmerge { index, from, into } => {
+                if let Some(card) = self
+                    .card_data
+                    .iter_mut()
+                    .find(|card| card.uuid == into.uuid)
+                {
+                    scanning::unmerge_card(card, &into, &from);
+                }
+                self.reinsert_card(index, *from);
+            }
+        }
+    }
+
+    /// Puts a removed card back at its old place, unless a scan has added it back already
+    fn reinsert_card(&mut self, index: usize, card: Card) {
+        if !self
+            .card_data
+            .iter()
+            .any(|existing| existing.uuid == card.uuid)
+        {
+            let index = index.min(self.card_data.len());
+            self.card_data.insert(index, card);
+        }
+    }
+
+    /// Switches to another page, leaving the settings page counts as finishing the rename
+    fn show_page(&mut self, page: usize) {
+        self.commit_rename();
+        self.current = page;
+    }
+
+    /// Applies the card name draft if it's valid and queues a save. An invalid draft is dropped
+    fn commit_rename(&mut self) {
+        let draft = match self.rename.take() {
+            Some(draft) => draft,
+            None => return,
+        };
+
+        let name = match utils::validate_card_name(&draft.name, &draft.card_uuid, &self.card_data) {
+            Ok(name) => name,
+            Err(problem) => {
+                self.settings_status = Some(problem);
+                return;
+            }
+        };
+
+        if let Some(card) = self
+            .card_data
+            .iter_mut()
+            .find(|card| card.uuid == draft.card_uuid)
+        {
+            if card.name != name {
+                let old_name = std::mem::replace(&mut card.name, name);
+                self.settings_status = Some(format!("Renamed {old_name} to {}", card.name));
+                self.rename_history.push((draft.card_uuid, old_name));
+                self.save_queue.save(&self.card_data);
+            }
+        }
+    }
+
+    /// Applies a change made on the settings page straight away and saves the preferences
+    fn change_preference(&mut self, change: PreferenceChange) -> Command<Message> {
+        let command = match &change {
+            PreferenceChange::DefaultView(list_view) => {
+                self.list_view = *list_view;
+                self.load_artwork()
+            }
+            PreferenceChange::DefaultSort(sort_by) => {
+                self.sort_by = *sort_by;
+                Command::none()
+            }
+            PreferenceChange::WindowSize(size) if !self.fullscreen => {
+                window::resize(size.width, size.height)
+            }
+            PreferenceChange::AddSearchRoot(_) => {
+                self.search_root_draft.clear();
+                Command::none()
+            }
+            _ => Command::none(),
+        };
+
+        self.preferences.apply(change);
+        if let Err(e) = preferences::save(&self.preferences) {
+            self.report([e]);
+        }
+
+        command
+    }
+
+    /// Moves the focus around the window or presses the focused button
+    fn gamepad_input(&mut self, button: GamepadButton) -> Command<Message> {
+        // While the keyboard is open the controller only types
+        if let Some(keyboard) = &mut self.keyboard {
+            return match keyboard.gamepad_input(button) {
+                Some(key) => self.update(Message::KeyboardKey(key)),
+                None => Command::none(),
+            };
+        }
+
+        let content = self.content_focusables();
+
+        let focus = match self.focus {
+            Some(focus) => focus,
+            None => {
+                // The first press only shows where the focus is
+                let zone = if content.targets.is_empty() {
+                    FocusZone::Controls
+                } else {
+                    FocusZone::Content
+                };
+                self.focus = Some(Focus { zone, index: 0 });
+                return Command::none();
+            }
+        };
+
+        let content_empty = content.targets.is_empty();
+        let (targets, grid) = match focus.zone {
+            FocusZone::Controls => (
+                self.control_buttons()
+                    .into_iter()
+                    .map(|(_, message)| message)
+                    .collect(),
+                None,
+            ),
+            FocusZone::Content => (content.targets, content.grid),
+        };
+        let last = targets.len().saturating_sub(1);
+        let index = focus.index.min(last);
+        // The grid the focus is in, if it's on one
+        let grid = grid.filter(|grid| grid.contains(index));
+
+        let moved_to = match button {
+            GamepadButton::Up => match grid {
+                Some(grid) if index - grid.first >= grid.columns => index - grid.columns,
+                _ => index.saturating_sub(1),
+            },
+            GamepadButton::Down => match grid {
+                Some(grid) => grid_down(grid, index).min(last),
+                None => (index + 1).min(last),
+            },
+            GamepadButton::Left
+                if grid.is_some_and(|grid| !(index - grid.first).is_multiple_of(grid.columns)) =>
+            {
+                index - 1
+            }
+            GamepadButton::Right if grid.is_some() => match grid {
+                Some(grid)
+                    if !(index - grid.first + 1).is_multiple_of(grid.columns)
+                        && grid.contains(index + 1) =>
+                {
+                    index + 1
+                }
+                _ => index,
+            },
+            GamepadButton::PageUp => index.saturating_sub(PAGE_SCROLL_ITEMS),
+            GamepadButton::PageDown => (index + PAGE_SCROLL_ITEMS).min(last),
+            GamepadButton::Left => {
+                self.focus = Some(Focus {
+                    zone: FocusZone::Controls,
+                    index: 0,
+                });
+                return Command::none();
+            }
+            GamepadButton::Right => {
+                if !content_empty {
+                    self.focus = Some(Focus {
+                        zone: FocusZone::Content,
+                        index: 0,
+                    });
+                }
+                return Command::none();
+            }
+            GamepadButton::Confirm => {
+                return match targets.get(index) {
+                    Some(message) => self.update(message.clone()),
+                    None => Command::none(),
+                };
+            }
+            GamepadButton::Back => {
+                if self.current != 0 {
+                    self.show_page(0);
+                    self.focus = Some(Focus {
+                        zone: FocusZone::Content,
+                        index: 0,
+                    });
+                } else {
+                    self.focus = Some(Focus {
+                        zone: FocusZone::Controls,
+                        index: 0,
+                    });
+                }
+                return Command::none();
+            }
+            GamepadButton::PreviousPage | GamepadButton::NextPage => {
+                // Switch between the list and settings pages
+                self.show_page(if self.current == 0 { 1 } else { 0 });
+                self.focus = Some(Focus {
+                    zone: FocusZone::Content,
+                    index: 0,
+                });
+                return Command::none();
+            }
+        };
+
+        self.focus = Some(Focus {
+            zone: focus.zone,
+            index: moved_to,
+        });
+
+        if focus.zone == FocusZone::Content && self.current == 0 && last > 0 {
+            // Keep the focused game on screen, roughly
+            return scrollable::snap_to(utils::list_scrollable_id(), moved_to as f32 / last as f32);
+        }
+
+        Command::none()
+    }
+
+    /// Where the selected game's folder is, None if the card's location isn't known
+    fn selected_game_path(&self) -> Option<PathBuf> {
+        let game_id = self.selected_game.as_ref()?;
+        let (card, game) = utils::find_game(&self.card_data, game_id)?;
+        card.game_path(game_id.library, game)
+    }
+
+    /// Changes the selected game's saved data and writes the list to the save file
+    fn edit_selected_game(&mut self, edit: impl FnOnce(&mut Game)) {
+        let game_id = match &self.selected_game {
+            Some(game_id) => game_id,
+            None => return,
+        };
+
+        let game = self
+            .card_data
+            .iter_mut()
+            .find(|card| card.uuid == game_id.card_uuid)
+            .and_then(|card| card.library_games_mut(game_id.library))
+            .and_then(|games| games.iter_mut().find(|game| game.name == game_id.name));
+
+        if let Some(game) = game {
+            edit(game);
+            self.save_queue.save(&self.card_data);
+        }
     }
 }
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub enum Message {
+    /// Cover art for the grid finished loading, None for the games Steam has no art for
+    ArtworkLoaded(Vec<(u32, Option<Thumbnail>)>, Vec<Error>),
     /// Activates the scan card function manually, called when 'Scan Card' is clicked
     ScanCard,
+    /// The background scan is done, its cards go into the list
+    ScanFinished(Result<ScanResult, Error>),
+    /// A card was taken out, the inserted cards are looked up again
+    CardsChanged,
+    /// Hides one of the problems shown above the page, it stays in the error log
+    DismissToast(usize),
+    ShowErrorLog,
+    ClearErrorLog,
     /// Exit the application, called when 'Exit' is clicked
     Exit,
-    /// Attempt at a fullscreen button and to fix the issue with the app's resolution while on the desktop and in game mode
+    /// Switch between fullscreen and a window of the size picked in the settings
     Fullscreen,
     SearchInput(String),
     Settings,
     Home,
+    /// Typing into a card's name field, (name, UUID). Only changes the draft until it's committed
     ChangeCardName(String, String),
+    /// Apply the card name draft, called when Enter is pressed in the field
+    CommitRename,
+    /// Throw away the card name draft, called when Escape is pressed
+    CancelRename,
+    /// Put back the name from before the last rename
+    UndoRename,
+    /// Pick the card with the UUID as the old entry to merge into another card
+    StartMerge(String),
+    /// Ask the user to confirm a forget, archive or merge
+    RequestCardAction(CardAction),
+    ConfirmCardAction,
+    CancelCardAction,
+    /// Reverse the last confirmed card action, only what it changed, so edits made since are kept
+    UndoCardAction,
+    /// Save a printable label for the card with the passed in UUID
+    CreateLabel(String),
+    LabelImportInput(String),
+    /// Import the card described by the pasted label data
+    ImportLabel,
+    SetListView(ListView),
+    SetSortBy(SortBy),
+    /// Show or hide the games of the card with the passed in UUID
+    ToggleCard(String),
+    ExpandAllCards,
+    CollapseAllCards,
+    /// Open the game page for the game, called when a game in the list is clicked
+    ShowGame(GameId),
+    /// Open the selected game's folder in the file manager
+    OpenGameFolder,
+    /// Copy the selected game's folder path to the clipboard
+    CopyGamePath,
+    ToggleFavourite,
+    NoteInput(String),
+    /// Save the note written on the game page to the selected game
+    SaveNote,
+    /// Copy the selected game to the library at the index in `transfer_targets`, moving it if the bool is set
+    StartTransfer(usize, bool),
+    TransferProgress(Progress),
+    TransferFinished(Result<(), Error>),
+    ShowPlanner,
+    /// Open the page comparing Steam's library on this Deck with the saved cards
+    ShowSteamLibrary,
+    SteamLibraryLoaded(Result<Vec<LibraryEntry>, Error>, Vec<Error>),
+    ToggleOtherCardsOnly,
+    PlannerInput(String),
+    /// Rank the cards for the size or app ID typed on the planner page
+    RunPlanner,
+    /// Open the planner for a copy of the game
+    PlanGame(GameId),
+    /// Open the card info page for the card with the UUID
+    ShowCardInfo(String),
+    PurchaseNoteInput(String),
+    /// Open the uninstall page for the card with the UUID
+    ShowUninstall(String),
+    UninstallPlanReady(String, Vec<Removal>),
+    /// Select or unselect the game at the index on the uninstall page
+    ToggleUninstallGame(usize),
+    RequestUninstall,
+    ConfirmUninstall,
+    CancelUninstall,
+    UninstallFinished(String, Vec<Removal>, Result<(), Error>),
+    /// Open the add to Steam page for the card with the UUID
+    ShowShortcuts(String),
+    ShortcutCandidatesFound(String, Result<Vec<Candidate>, Error>),
+    /// Select or unselect the game at the index on the add to Steam page
+    ToggleShortcutGame(usize),
+    /// Switch the game at the index between starting through its launcher and its executable
+    ToggleShortcutLaunch(usize),
+    AddShortcuts,
+    ShortcutsAdded(Result<Added, Error>),
+    LeftoversFound(String, Vec<Leftover>),
+    /// Ask before removing the leftovers, true for the Proton prefixes which are removed on their own
+    RequestRemoveLeftovers(bool),
+    ConfirmRemoveLeftovers(bool),
+    /// (card UUID, the space freed or the problem, the leftovers still on the card)
+    LeftoversRemoved(String, Result<u64, Error>, Vec<Leftover>),
+    /// Save the purchase note written on the card info page
+    SavePurchaseNote,
+    /// Run the speed test on the card with the UUID, with the write test if the bool is set
+    RunBenchmark(String, bool),
+    BenchmarkFinished(String, Result<BenchmarkResult, Error>),
+    /// Check the games on the card with the UUID for missing or damaged files
+    VerifyCard(String),
+    VerifyFinished(String, VerifyReport),
+    /// A press on the controller, see the gamepad module
+    Gamepad(GamepadButton),
+    /// Show the on-screen keyboard for a text field
+    OpenKeyboard(KeyboardTarget),
+    KeyboardKey(Key),
+    /// A preference changed on the settings page
+    ChangePreference(PreferenceChange),
+    SearchRootInput(String),
+}
+
+/// Where Down moves the focus from a grid button: the one below it, the last button if the row below is
+/// shorter, or the first button after the grid from the last row
+fn grid_down(grid: FocusGrid, index: usize) -> usize {
+    let end = grid.first + grid.count;
+    let row = (index - grid.first) / grid.columns;
+    let last_row = (grid.count - 1) / grid.columns;
+
+    if index + grid.columns < end {
+        index + grid.columns
+    } else if row < last_row {
+        end - 1
+    } else {
+        end
+    }
 }
@@ -0,0 +1,68 @@
+use crate::app::Message;
+use iced::futures::channel::mpsc;
+use iced::futures::StreamExt;
+use iced::{subscription, Subscription};
+use sdscanner::scanning;
+use std::collections::HashSet;
+use std::{thread, time::Duration};
+
+/// How often lsblk is checked for newly inserted cards
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+enum State {
+    Starting,
+    Listening(mpsc::UnboundedReceiver<Message>),
+}
+
+/// Watches for inserted cards on a background thread and sends `Message::ScanCard` when a new one shows up,
+/// or `Message::CardsChanged` when one is taken out.
+/// Only used while auto rescan is turned on in the settings
+pub fn subscription() -> Subscription<Message> {
+    subscription::unfold("card-watcher", State::Starting, |state| async move {
+        match state {
+            State::Starting => {
+                let (sender, receiver) = mpsc::unbounded();
+                thread::spawn(move || watch_cards(sender));
+
+                (None, State::Listening(receiver))
+            }
+            State::Listening(mut receiver) => match receiver.next().await {
+                Some(message) => (Some(message), State::Listening(receiver)),
+                None => iced::futures::future::pending().await,
+            },
+        }
+    })
+}
+
+/// The UUIDs of the currently mounted cards
+fn inserted_cards() -> HashSet<String> {
+    scanning::discover_cards()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|card| card.uuid)
+        .collect()
+}
+
+fn watch_cards(sender: mpsc::UnboundedSender<Message>) {
+    let mut inserted = inserted_cards();
+
+    // The receiver is dropped once auto rescan is turned off
+    while !sender.is_closed() {
+        thread::sleep(POLL_INTERVAL);
+
+        let now_inserted = inserted_cards();
+        // Removed cards keep their saved data, only a new card needs a scan
+        let message = if now_inserted.difference(&inserted).next().is_some() {
+            Message::ScanCard
+        } else if inserted.difference(&now_inserted).next().is_some() {
+            Message::CardsChanged
+        } else {
+            continue;
+        };
+        inserted = now_inserted;
+
+        if sender.unbounded_send(message).is_err() {
+            return;
+        }
+    }
+}
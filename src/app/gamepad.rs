@@ -0,0 +1,195 @@
+use crate::app::Message;
+use evdev::{AbsoluteAxisType, Device, InputEventKind, Key};
+use iced::futures::channel::mpsc;
+use iced::futures::StreamExt;
+use iced::{subscription, Subscription};
+use std::{thread, time::Duration};
+
+/// How often to look for a controller again when none is connected
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+
+/// How far a stick or trigger has to be pushed, out of 1.0, before it counts as a press
+const AXIS_THRESHOLD: f32 = 0.6;
+
+/// The controller inputs the app reacts to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamepadButton {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// A
+    Confirm,
+    /// B
+    Back,
+    /// Left bumper
+    PreviousPage,
+    /// Right bumper
+    NextPage,
+    /// Left trigger
+    PageUp,
+    /// Right trigger
+    PageDown,
+}
+
+enum State {
+    Starting,
+    Listening(mpsc::UnboundedReceiver<GamepadButton>),
+}
+
+/// Reads the controller on a background thread and sends its presses to the app as `Message::Gamepad`
+pub fn subscription() -> Subscription<Message> {
+    subscription::unfold("gamepad", State::Starting, |state| async move {
+        match state {
+            State::Starting => {
+                let (sender, receiver) = mpsc::unbounded();
+                thread::spawn(move || read_gamepads(sender));
+
+                (None, State::Listening(receiver))
+            }
+            State::Listening(mut receiver) => match receiver.next().await {
+                Some(button) => (Some(Message::Gamepad(button)), State::Listening(receiver)),
+                // The reading thread only stops if the app is closing, nothing left to do
+                None => iced::futures::future::pending().await,
+            },
+        }
+    })
+}
+
+/// Finds a controller and forwards its presses, looking for a new one whenever it disconnects
+fn read_gamepads(sender: mpsc::UnboundedSender<GamepadButton>) {
+    loop {
+        if let Some(device) = find_gamepad() {
+            // read_device only returns Ok once the app stops listening, an error means the controller disconnected
+            if read_device(device, &sender).is_ok() {
+                return;
+            }
+        }
+
+        thread::sleep(RECONNECT_DELAY);
+    }
+}
+
+/// The first input device with a face button, in Game Mode this is Steam's virtual controller
+fn find_gamepad() -> Option<Device> {
+    evdev::enumerate().map(|(_, device)| device).find(|device| {
+        device
+            .supported_keys()
+            .is_some_and(|keys| keys.contains(Key::BTN_SOUTH))
+    })
+}
+
+/// Tracks whether a stick or trigger is currently pushed past the threshold, so holding it only sends one press
+struct Axis {
+    minimum: i32,
+    maximum: i32,
+    pushed: i8,
+}
+
+impl Axis {
+    /// Returns -1, 0 or 1 when the axis moves into a new position, None while it stays where it was
+    fn update(&mut self, value: i32) -> Option<i8> {
+        let range = (self.maximum - self.minimum).max(1) as f32;
+        let centered = (value - self.minimum) as f32 / range * 2.0 - 1.0;
+
+        let pushed = if centered > AXIS_THRESHOLD {
+            1
+        } else if centered < -AXIS_THRESHOLD {
+            -1
+        } else {
+            0
+        };
+
+        if pushed == self.pushed {
+            None
+        } else {
+            self.pushed = pushed;
+            Some(pushed)
+        }
+    }
+}
+
+/// Sends the device's presses until it disconnects or the app stops listening
+fn read_device(
+    mut device: Device,
+    sender: &mpsc::UnboundedSender<GamepadButton>,
+) -> std::io::Result<()> {
+    let abs_state = device.get_abs_state()?;
+    let axis = |axis_type: AbsoluteAxisType| {
+        let info = abs_state[axis_type.0 as usize];
+        Axis {
+            minimum: info.minimum,
+            maximum: info.maximum,
+            pushed: 0,
+        }
+    };
+
+    let mut stick_x = axis(AbsoluteAxisType::ABS_X);
+    let mut stick_y = axis(AbsoluteAxisType::ABS_Y);
+    let mut hat_x = axis(AbsoluteAxisType::ABS_HAT0X);
+    let mut hat_y = axis(AbsoluteAxisType::ABS_HAT0Y);
+    let mut left_trigger = axis(AbsoluteAxisType::ABS_Z);
+    let mut right_trigger = axis(AbsoluteAxisType::ABS_RZ);
+
+    loop {
+        for event in device.fetch_events()? {
+            let button = match event.kind() {
+                // Only react to the press, not the release
+                InputEventKind::Key(key) if event.value() == 1 => match key {
+                    Key::BTN_SOUTH => Some(GamepadButton::Confirm),
+                    Key::BTN_EAST => Some(GamepadButton::Back),
+                    Key::BTN_TL => Some(GamepadButton::PreviousPage),
+                    Key::BTN_TR => Some(GamepadButton::NextPage),
+                    Key::BTN_TL2 => Some(GamepadButton::PageUp),
+                    Key::BTN_TR2 => Some(GamepadButton::PageDown),
+                    Key::BTN_DPAD_UP => Some(GamepadButton::Up),
+                    Key::BTN_DPAD_DOWN => Some(GamepadButton::Down),
+                    Key::BTN_DPAD_LEFT => Some(GamepadButton::Left),
+                    Key::BTN_DPAD_RIGHT => Some(GamepadButton::Right),
+                    _ => None,
+                },
+                InputEventKind::AbsAxis(axis_type) => {
+                    let (axis, negative, positive) = match axis_type {
+                        AbsoluteAxisType::ABS_X => {
+                            (&mut stick_x, GamepadButton::Left, GamepadButton::Right)
+                        }
+                        AbsoluteAxisType::ABS_Y => {
+                            (&mut stick_y, GamepadButton::Up, GamepadButton::Down)
+                        }
+                        AbsoluteAxisType::ABS_HAT0X => {
+                            (&mut hat_x, GamepadButton::Left, GamepadButton::Right)
+                        }
+                        AbsoluteAxisType::ABS_HAT0Y => {
+                            (&mut hat_y, GamepadButton::Up, GamepadButton::Down)
+                        }
+                        // Triggers rest at their minimum, so only the positive direction means anything
+                        AbsoluteAxisType::ABS_Z => (
+                            &mut left_trigger,
+                            GamepadButton::PageUp,
+                            GamepadButton::PageUp,
+                        ),
+                        AbsoluteAxisType::ABS_RZ => (
+                            &mut right_trigger,
+                            GamepadButton::PageDown,
+                            GamepadButton::PageDown,
+                        ),
+                        _ => continue,
+                    };
+
+                    match axis.update(event.value()) {
+                        Some(1) => Some(positive),
+                        Some(-1) if negative != positive => Some(negative),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+
+            if let Some(button) = button {
+                if sender.unbounded_send(button).is_err() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
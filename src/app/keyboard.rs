@@ -0,0 +1,337 @@
+use crate::app::gamepad::GamepadButton;
+use crate::app::theming;
+use crate::app::Message;
+use iced::widget::{button, column, container, row, text, Row};
+use iced::{Alignment, Element, Length, Point, Rectangle, Renderer, Theme};
+use iced_native::widget::{Operation, Tree, Widget};
+use iced_native::{event, layout, mouse, overlay, renderer, touch};
+use iced_native::{Clipboard, Event, Layout, Shell};
+
+/// The text field the on-screen keyboard is typing into
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyboardTarget {
+    Search,
+    /// The name of the card with the UUID
+    CardName(String),
+    LabelImport,
+    Note,
+    /// The purchase note on the card info page
+    PurchaseNote,
+    /// The extra library folder field on the settings page
+    SearchRoot,
+    /// The game size field on the space planner page
+    PlannerSize,
+}
+
+/// A single key on the on-screen keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    /// Types a whole piece of text at once, used for the search qualifiers
+    Chip(&'static str),
+    Shift,
+    Space,
+    Backspace,
+    Clear,
+    /// Close the keyboard
+    Done,
+}
+
+const LETTER_ROWS: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl'", "zxcvbnm-.:"];
+
+/// Quick qualifier keys shown above the letters while searching
+const SEARCH_CHIPS: [&str; 7] = [
+    "card:",
+    "lib:steam",
+    "lib:lutris",
+    "lib:heroic",
+    "size:>",
+    "appid:",
+    "notplayed:",
+];
+
+/// Extra symbols for naming cards, writing notes and typing folders
+const NAMING_SYMBOLS: [char; 9] = ['_', '&', '!', '#', '(', ')', '+', ',', '/'];
+
+/// The on-screen keyboard's state, shown at the bottom of the window while a text field is being edited
+pub struct Keyboard {
+    pub target: KeyboardTarget,
+    shift: bool,
+    /// The (row, column) of the key the controller is on
+    cursor: (usize, usize),
+}
+
+impl Keyboard {
+    pub fn new(target: KeyboardTarget) -> Self {
+        Keyboard {
+            target,
+            shift: false,
+            cursor: (0, 0),
+        }
+    }
+
+    /// The keys laid out in rows. The search layout gets the qualifier chips, the naming layout more symbols
+    fn rows(&self) -> Vec<Vec<Key>> {
+        let mut rows: Vec<Vec<Key>> = vec![];
+
+        if self.target == KeyboardTarget::Search {
+            rows.push(SEARCH_CHIPS.iter().map(|chip| Key::Chip(chip)).collect());
+        }
+
+        for letters in LETTER_ROWS {
+            rows.push(letters.chars().map(Key::Char).collect());
+        }
+
+        if self.target != KeyboardTarget::Search {
+            rows.push(NAMING_SYMBOLS.iter().map(|c| Key::Char(*c)).collect());
+        }
+
+        rows.push(vec![
+            Key::Shift,
+            Key::Space,
+            Key::Backspace,
+            Key::Clear,
+            Key::Done,
+        ]);
+
+        rows
+    }
+
+    /// Moves the cursor with the controller, returns the key to press when A is pressed
+    pub fn gamepad_input(&mut self, gamepad_button: GamepadButton) -> Option<Key> {
+        let rows = self.rows();
+        let (row, column) = self.cursor;
+
+        match gamepad_button {
+            GamepadButton::Up | GamepadButton::PageUp => {
+                self.cursor.0 = row.saturating_sub(1);
+            }
+            GamepadButton::Down | GamepadButton::PageDown => {
+                self.cursor.0 = (row + 1).min(rows.len() - 1);
+            }
+            GamepadButton::Left => self.cursor.1 = column.saturating_sub(1),
+            GamepadButton::Right => self.cursor.1 = column + 1,
+            GamepadButton::Confirm => return Some(self.focused_key(&rows)),
+            GamepadButton::Back => return Some(Key::Done),
+            GamepadButton::PreviousPage => return Some(Key::Backspace),
+            GamepadButton::NextPage => return Some(Key::Space),
+        }
+
+        // Rows have different lengths, keep the cursor on a key
+        self.cursor.1 = self.cursor.1.min(rows[self.cursor.0].len() - 1);
+        None
+    }
+
+    fn focused_key(&self, rows: &[Vec<Key>]) -> Key {
+        let (row, column) = self.cursor;
+        rows[row][column.min(rows[row].len() - 1)]
+    }
+
+    /// Applies a key press to the text, returns None if the key doesn't change the text
+    pub fn press(&mut self, key: Key, text: &str) -> Option<String> {
+        let mut text = text.to_string();
+
+        match key {
+            Key::Char(c) => {
+                if self.shift {
+                    text.extend(c.to_uppercase());
+                    self.shift = false;
+                } else {
+                    text.push(c);
+                }
+            }
+            Key::Chip(chip) => {
+                if !text.is_empty() && !text.ends_with(' ') {
+                    text.push(' ');
+                }
+                text.push_str(chip);
+            }
+            Key::Space => text.push(' '),
+            Key::Backspace => {
+                text.pop();
+            }
+            Key::Clear => text.clear(),
+            Key::Shift => {
+                self.shift = !self.shift;
+                return None;
+            }
+            Key::Done => return None,
+        }
+
+        Some(text)
+    }
+
+    /// The keyboard panel, `text` is what's been typed into the target field so far
+    pub fn view<'a>(&self, label: &str, typed: &str) -> Element<'a, Message> {
+        let rows = self.rows();
+        let focused = self.focused_key(&rows);
+
+        let mut keyboard = column![text(format!("{label}: {typed}_")).size(30)]
+            .spacing(6)
+            .padding(10)
+            .align_items(Alignment::Center);
+
+        for keys in rows {
+            let key_row = keys
+                .into_iter()
+                .fold(row![].spacing(6), |key_row: Row<Message>, key| {
+                    let (label, width) = match key {
+                        Key::Char(c) if self.shift => (c.to_uppercase().collect(), 60),
+                        Key::Char(c) => (c.to_string(), 60),
+                        Key::Chip(chip) => (chip.to_string(), 140),
+                        Key::Shift if self.shift => (String::from("SHIFT"), 120),
+                        Key::Shift => (String::from("Shift"), 120),
+                        Key::Space => (String::from("Space"), 240),
+                        Key::Backspace => (String::from("Delete"), 120),
+                        Key::Clear => (String::from("Clear"), 120),
+                        Key::Done => (String::from("Done"), 120),
+                    };
+
+                    key_row.push(
+                        button(text(label).size(25))
+                            .width(Length::Units(width))
+                            .padding(8)
+                            .style(theming::focus_style(key == focused, false))
+                            .on_press(Message::KeyboardKey(key)),
+                    )
+                });
+            keyboard = keyboard.push(key_row);
+        }
+
+        container(keyboard).width(Length::Fill).center_x().into()
+    }
+}
+
+/// Wraps a text field so tapping or clicking it opens the on-screen keyboard. iced 0.6's text inputs don't
+/// report when they gain focus, so the press is caught here and then passed on to the field
+pub struct KeyboardField<'a> {
+    field: Element<'a, Message>,
+    target: KeyboardTarget,
+}
+
+impl<'a> KeyboardField<'a> {
+    pub fn new(field: impl Into<Element<'a, Message>>, target: KeyboardTarget) -> Self {
+        KeyboardField {
+            field: field.into(),
+            target,
+        }
+    }
+}
+
+impl<'a> Widget<Message, Renderer> for KeyboardField<'a> {
+    fn width(&self) -> Length {
+        self.field.as_widget().width()
+    }
+
+    fn height(&self) -> Length {
+        self.field.as_widget().height()
+    }
+
+    fn layout(&self, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        self.field.as_widget().layout(renderer, limits)
+    }
+
+    fn draw(
+        &self,
+        state: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+    ) {
+        self.field.as_widget().draw(
+            &state.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor_position,
+            viewport,
+        );
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.field)]
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(std::slice::from_ref(&self.field));
+    }
+
+    fn operate(
+        &self,
+        state: &mut Tree,
+        layout: Layout<'_>,
+        operation: &mut dyn Operation<Message>,
+    ) {
+        self.field
+            .as_widget()
+            .operate(&mut state.children[0], layout, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        state: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+    ) -> event::Status {
+        let pressed_at = match &event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => Some(cursor_position),
+            Event::Touch(touch::Event::FingerPressed { position, .. }) => Some(*position),
+            _ => None,
+        };
+        if pressed_at.is_some_and(|position| layout.bounds().contains(position)) {
+            shell.publish(Message::OpenKeyboard(self.target.clone()));
+        }
+
+        self.field.as_widget_mut().on_event(
+            &mut state.children[0],
+            event,
+            layout,
+            cursor_position,
+            renderer,
+            clipboard,
+            shell,
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &Tree,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.field.as_widget().mouse_interaction(
+            &state.children[0],
+            layout,
+            cursor_position,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        state: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+    ) -> Option<overlay::Element<'b, Message, Renderer>> {
+        self.field
+            .as_widget_mut()
+            .overlay(&mut state.children[0], layout, renderer)
+    }
+}
+
+impl<'a> From<KeyboardField<'a>> for Element<'a, Message> {
+    fn from(field: KeyboardField<'a>) -> Self {
+        Element::new(field)
+    }
+}
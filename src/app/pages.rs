@@ -1,37 +1,82 @@
+use crate::app::keyboard::KeyboardTarget;
 use crate::app::utils;
-use crate::app::utils::{long_settings_label, settings_label};
-use crate::app::Message;
-use crate::scanning::Card;
-use iced::widget::{column, container, row, text, text_input, Column};
-use iced::{Element, Length};
+use crate::app::utils::{long_settings_label, settings_label, Focusables};
+use crate::app::{theming, App, CardAction, Message};
+use iced::widget::{
+    button, column, container, pick_list, row, scrollable, text, text_input, Column, Scrollable,
+};
+use iced::{Alignment, Element, Length};
+use sdscanner::cleanup::{self, Leftover};
+use sdscanner::preferences::{DeepScan, PreferenceChange, ThemeChoice, WindowSize, FONT_SCALES};
+use sdscanner::scanning::{self, Library};
+use sdscanner::shortcuts::Launch;
+use sdscanner::steam::Location;
+use std::path::PathBuf;
+
+/// The list layouts and sorts are saved with the preferences
+pub use sdscanner::preferences::{ListView, SortBy};
+
+/// How many changed files of each kind the game page lists
+const MAX_LISTED_CHANGES: usize = 20;
 
 pub enum Page {
     List,
     Settings,
+    /// Details and actions for the game selected in the list
+    Game,
+    /// What the card picked on the settings page reports about itself
+    CardInfo,
+    /// Picking games to remove from a card, with how much space they free
+    Uninstall,
+    /// Which card a game fits on
+    Planner,
+    /// The games Steam on this Deck knows about and which card they're on
+    SteamLibrary,
+    /// Picking a card's Lutris and Heroic games to add to Steam as shortcuts
+    Shortcuts,
+    /// Every problem reported since the app was opened
+    ErrorLog,
+}
+
+/// The option after `current` in `options`, wrapping back around to the first
+fn next_option<T: Copy + PartialEq>(options: &[T], current: T) -> T {
+    let index = options.iter().position(|option| *option == current);
+    options[index.map_or(0, |index| (index + 1) % options.len())]
 }
 
 impl<'a> Page {
-    /// view() probably shouldn't be designed to require card_data or search_term but it works for now
-    pub fn view(&'a self, card_data: &'a Vec<Card>, search_term: &'a str) -> Element<Message> {
+    /// Pages read whatever state they need straight from the App,
+    /// their buttons are added to `focus` so the controller can move between them
+    pub fn view(&'a self, app: &'a App, focus: &mut Focusables) -> Element<'a, Message> {
         match self {
-            Page::List => Self::list(card_data, search_term).into(),
-            Page::Settings => Self::settings(card_data).into(),
+            Page::List => Self::list(app, focus).into(),
+            Page::Settings => Self::settings(app, focus).into(),
+            Page::Game => Self::game(app, focus).into(),
+            Page::CardInfo => Self::card_info(app, focus).into(),
+            Page::Uninstall => Self::uninstall(app, focus).into(),
+            Page::Planner => Self::planner(app, focus).into(),
+            Page::SteamLibrary => Self::steam_library(app, focus).into(),
+            Page::Shortcuts => Self::shortcuts(app, focus).into(),
+            Page::ErrorLog => Self::error_log(app, focus).into(),
         }
     }
 
-    fn list(list: &'a Vec<Card>, search_term: &'a str) -> Column<'a, Message> {
+    fn list(app: &'a App, focus: &mut Focusables) -> Column<'a, Message> {
+        let list = &app.card_data;
         let mut element_list: Vec<Element<Message>> = vec![container(row(vec![
-            text_input("Filter Search...", search_term, |text_value| {
-                Message::SearchInput(text_value)
-            })
-            .size(30)
-            .width(Length::FillPortion(2))
-            .padding(4)
-            .into(),
+            focus.text_field(
+                text_input("Filter Search...", &app.search_term, |text_value| {
+                    Message::SearchInput(text_value)
+                })
+                .size(30)
+                .width(Length::FillPortion(2))
+                .padding(4),
+                KeyboardTarget::Search,
+            ),
             container(
                 text(format!(
                     "Current Card: {}",
-                    if let Some(card_name) = utils::get_card_name(list) {
+                    if let Some(card_name) = utils::get_card_name(list, &app.inserted_cards) {
                         card_name
                     } else {
                         String::from("No Card Detected")
@@ -46,34 +91,1432 @@ impl<'a> Page {
         .padding(4)
         .into()];
 
-        element_list.push(utils::create_card_and_games_list(list, search_term).into());
+        let mut view_controls = row![
+            text("View:").size(25),
+            pick_list(
+                &ListView::ALL[..],
+                Some(app.list_view),
+                Message::SetListView
+            ),
+            text("Sort:").size(25),
+            pick_list(&SortBy::ALL[..], Some(app.sort_by), Message::SetSortBy),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+
+        if app.list_view == ListView::Cards {
+            view_controls = view_controls
+                .push(focus.button(text("Expand All"), Message::ExpandAllCards))
+                .push(focus.button(text("Collapse All"), Message::CollapseAllCards));
+        }
+
+        element_list.push(container(view_controls).padding(4).into());
+
+        element_list
+            .push(utils::create_card_and_games_list(list, &app.list_options(), focus).into());
         column(element_list).width(Length::Fill)
     }
 
-    // TODO
-    fn settings(list_data: &Vec<Card>) -> Column<Message> {
+    fn settings(app: &'a App, focus: &mut Focusables) -> Scrollable<'a, Message> {
         let mut element_list: Vec<Element<Message>> = vec![
-            container(text("Settings - Work in Progress").size(40)).padding(2).into(),
+            container(text("Settings").size(40)).padding(2).into(),
+            container(text("Cards").size(30)).padding(2).into(),
             row(vec![
                 long_settings_label(text("Card Name").size(25)),
                 settings_label(text("ID").size(25)),
                 settings_label(text("Game(s)").size(25)),
+                settings_label(text("Label").size(25)),
+                settings_label(text("Speed").size(25)),
+                settings_label(text("Manage").size(25)),
             ])
             .into(),
         ];
 
-        for card in list_data {
+        if let Some(action) = &app.pending_card_action {
+            element_list.push(
+                row![
+                    text(app.card_action_prompt(action)).size(22),
+                    focus.button(text("Confirm"), Message::ConfirmCardAction),
+                    focus.button(text("Cancel"), Message::CancelCardAction),
+                ]
+                .spacing(10)
+                .padding(4)
+                .align_items(Alignment::Center)
+                .into(),
+            );
+        } else if let Some(from_uuid) = &app.merge_from {
+            element_list.push(
+                row![
+                    text(format!(
+                        "Pick the card to merge {} into with Merge Here",
+                        app.card_name(from_uuid)
+                    ))
+                    .size(22),
+                    focus.button(text("Cancel"), Message::CancelCardAction),
+                ]
+                .spacing(10)
+                .padding(4)
+                .align_items(Alignment::Center)
+                .into(),
+            );
+        }
+
+        for card in &app.card_data {
             let card_settings = row(vec![
-                long_settings_label(text_input(&card.name, &card.name, |user_input| {
-                    Message::ChangeCardName(user_input, card.uuid.clone())
-                })),
+                long_settings_label(
+                    focus.text_field(
+                        text_input(&card.name, &app.card_name_text(&card.uuid), |user_input| {
+                            Message::ChangeCardName(user_input, card.uuid.clone())
+                        })
+                        .on_submit(Message::CommitRename),
+                        KeyboardTarget::CardName(card.uuid.clone()),
+                    ),
+                ),
                 settings_label(text(&card.uuid[..4])),
-                settings_label(text(format!("{}", utils::card_games_count(&card)))),
+                settings_label(text(format!("{}", scanning::card_games_count(card)))),
+                settings_label(
+                    focus.button(text("Print"), Message::CreateLabel(card.uuid.clone())),
+                ),
+                settings_label(text(card.benchmarks.last().map_or_else(
+                    || String::from("Untested"),
+                    |result| format!("{:.0} MB/s", result.sequential_read),
+                ))),
+                Self::card_actions(app, card, focus),
             ]);
 
             element_list.push(card_settings.into())
         }
 
+        let mut undo_buttons = row![].spacing(10);
+        if let Some((_, old_name)) = app.rename_history.last() {
+            undo_buttons = undo_buttons.push(focus.button(
+                text(format!("Undo Rename (back to {old_name})")),
+                Message::UndoRename,
+            ));
+        }
+        if let Some((description, _)) = app.card_history.last() {
+            undo_buttons = undo_buttons.push(focus.button(
+                text(format!("Undo: {description}")),
+                Message::UndoCardAction,
+            ));
+        }
+        element_list.push(container(undo_buttons).padding(4).into());
+
+        element_list.push(
+            container(row(vec![
+                focus.text_field(
+                    text_input(
+                        "Paste label QR code data...",
+                        &app.label_import,
+                        Message::LabelImportInput,
+                    )
+                    .on_submit(Message::ImportLabel)
+                    .padding(4)
+                    .width(Length::Units(400)),
+                    KeyboardTarget::LabelImport,
+                ),
+                focus.button(text("Import"), Message::ImportLabel).into(),
+            ]))
+            .padding(4)
+            .into(),
+        );
+
+        if let Some(status) = &app.settings_status {
+            element_list.push(container(text(status)).padding(4).into());
+        }
+
+        element_list.push(container(text("Preferences").size(30)).padding(2).into());
+        element_list.extend(Self::preferences(app, focus));
+
+        scrollable(column(element_list))
+    }
+
+    /// The forget, archive and merge buttons of a card's row on the settings page
+    fn card_actions(
+        app: &App,
+        card: &scanning::Card,
+        focus: &mut Focusables,
+    ) -> Element<'a, Message> {
+        let uuid = card.uuid.clone();
+
+        let merge = match &app.merge_from {
+            Some(from_uuid) if *from_uuid == card.uuid => {
+                focus.button(text("Cancel"), Message::CancelCardAction)
+            }
+            Some(from_uuid) => focus.button(
+                text("Merge Here"),
+                Message::RequestCardAction(CardAction::Merge(from_uuid.clone(), uuid.clone())),
+            ),
+            None => focus.button(text("Merge"), Message::StartMerge(uuid.clone())),
+        };
+        let archive = if card.archived {
+            focus.button(
+                text("Unarchive"),
+                Message::RequestCardAction(CardAction::Unarchive(uuid.clone())),
+            )
+        } else {
+            focus.button(
+                text("Archive"),
+                Message::RequestCardAction(CardAction::Archive(uuid.clone())),
+            )
+        };
+        let forget = focus.button(
+            text("Forget"),
+            Message::RequestCardAction(CardAction::Forget(uuid)),
+        );
+
+        let info = focus.button(text("Info"), Message::ShowCardInfo(card.uuid.clone()));
+
+        container(row![info, archive, merge, forget].spacing(6))
+            .padding(5)
+            .style(theming::SETTINGS_LABEL_CONTAINER_STYLE)
+            .height(Length::Units(60))
+            .into()
+    }
+
+    /// The preference rows of the settings page, each value is a button that switches to the next option
+    fn preferences(app: &'a App, focus: &mut Focusables) -> Vec<Element<'a, Message>> {
+        let preferences = &app.preferences;
+        let on_off = |value: bool| String::from(if value { "On" } else { "Off" });
+
+        let rows = [
+            (
+                "Scan Lutris Libraries",
+                on_off(preferences.scan_lutris),
+                PreferenceChange::ScanLutris(!preferences.scan_lutris),
+            ),
+            (
+                "Scan Heroic Libraries",
+                on_off(preferences.scan_heroic),
+                PreferenceChange::ScanHeroic(!preferences.scan_heroic),
+            ),
+            (
+                "Add Up Game Sizes",
+                on_off(preferences.compute_sizes),
+                PreferenceChange::ComputeSizes(!preferences.compute_sizes),
+            ),
+            (
+                "Record Game Files While Scanning",
+                preferences.deep_scan.to_string(),
+                PreferenceChange::DeepScan(next_option(&DeepScan::ALL, preferences.deep_scan)),
+            ),
+            (
+                "Rescan When a Card Is Inserted",
+                on_off(preferences.auto_rescan),
+                PreferenceChange::AutoRescan(!preferences.auto_rescan),
+            ),
+            (
+                "Write an ID File to Each Card",
+                on_off(preferences.write_marker_file),
+                PreferenceChange::WriteMarkerFile(!preferences.write_marker_file),
+            ),
+            (
+                "Default View",
+                preferences.default_view.to_string(),
+                PreferenceChange::DefaultView(next_option(
+                    &ListView::ALL,
+                    preferences.default_view,
+                )),
+            ),
+            (
+                "Default Sort",
+                preferences.default_sort.to_string(),
+                PreferenceChange::DefaultSort(next_option(&SortBy::ALL, preferences.default_sort)),
+            ),
+            (
+                "Theme",
+                preferences.theme.to_string(),
+                PreferenceChange::Theme(next_option(&ThemeChoice::ALL, preferences.theme)),
+            ),
+            (
+                "Font Scale",
+                format!("{}%", preferences.font_scale),
+                PreferenceChange::FontScale(next_option(&FONT_SCALES, preferences.font_scale)),
+            ),
+            (
+                "Window Size",
+                preferences.window_size.to_string(),
+                PreferenceChange::WindowSize(next_option(
+                    &WindowSize::ALL,
+                    preferences.window_size,
+                )),
+            ),
+            (
+                "Start in Fullscreen",
+                on_off(preferences.fullscreen),
+                PreferenceChange::Fullscreen(!preferences.fullscreen),
+            ),
+        ];
+
+        let mut element_list: Vec<Element<Message>> = rows
+            .into_iter()
+            .map(|(label, value, change)| {
+                row![
+                    container(text(label).size(25)).width(Length::Units(400)),
+                    focus
+                        .button(text(value).size(25), Message::ChangePreference(change))
+                        .width(Length::Units(200))
+                        .padding(8),
+                ]
+                .align_items(Alignment::Center)
+                .padding(4)
+                .into()
+            })
+            .collect();
+
+        element_list.push(
+            container(text("Extra Folders to Search for Lutris and Heroic").size(25))
+                .padding(4)
+                .into(),
+        );
+
+        for (index, root) in preferences.extra_search_roots.iter().enumerate() {
+            element_list.push(
+                row![
+                    container(text(root.display()).size(25)).width(Length::Units(400)),
+                    focus
+                        .button(
+                            text("Remove").size(20),
+                            Message::ChangePreference(PreferenceChange::RemoveSearchRoot(index)),
+                        )
+                        .padding(8),
+                ]
+                .align_items(Alignment::Center)
+                .padding(4)
+                .into(),
+            );
+        }
+
+        let add_root = Message::ChangePreference(PreferenceChange::AddSearchRoot(PathBuf::from(
+            app.search_root_draft.trim(),
+        )));
+        element_list.push(
+            row![
+                focus.text_field(
+                    text_input(
+                        "Folder on the card, e.g. Games/Other",
+                        &app.search_root_draft,
+                        Message::SearchRootInput,
+                    )
+                    .on_submit(add_root.clone())
+                    .padding(4)
+                    .width(Length::Units(400)),
+                    KeyboardTarget::SearchRoot,
+                ),
+                focus.button(text("Add"), add_root),
+            ]
+            .spacing(6)
+            .padding(4)
+            .into(),
+        );
+
+        element_list
+    }
+
+    fn card_info(app: &'a App, focus: &mut Focusables) -> Scrollable<'a, Message> {
+        let card = match app
+            .card_data
+            .iter()
+            .find(|card| Some(&card.uuid) == app.selected_card.as_ref())
+        {
+            Some(card) => card,
+            None => {
+                return scrollable(column![text("This card is no longer on the list").size(40)])
+            }
+        };
+
+        let hardware = card.hardware.clone().unwrap_or_default();
+        let unknown = || String::from("Unknown");
+        let details = [
+            ("ID", card.uuid.clone()),
+            (
+                "Size",
+                card.capacity.map_or_else(unknown, scanning::format_size),
+            ),
+            (
+                "Manufacturer",
+                hardware.manufacturer().unwrap_or_else(unknown),
+            ),
+            ("OEM", hardware.oem().unwrap_or_else(unknown)),
+            ("Model", hardware.name.clone().unwrap_or_else(unknown)),
+            ("Made", hardware.date.clone().unwrap_or_else(unknown)),
+            (
+                "Spec Version",
+                hardware.spec_version().map_or_else(unknown, String::from),
+            ),
+            (
+                "Speed Class",
+                hardware.speed_class().unwrap_or_else(unknown),
+            ),
+            (
+                "Health",
+                hardware
+                    .health()
+                    .unwrap_or_else(|| String::from("Not reported by this card")),
+            ),
+            (
+                "Hardware ID",
+                card.hardware_id.clone().unwrap_or_else(unknown),
+            ),
+            ("CSD", hardware.csd.clone().unwrap_or_else(unknown)),
+            ("SCR", hardware.scr.clone().unwrap_or_else(unknown)),
+        ];
+
+        let mut element_list: Vec<Element<Message>> =
+            vec![container(text(&card.name).size(50)).padding(4).into()];
+
+        if card.hardware.is_none() {
+            element_list.push(
+                container(text(
+                    "The hardware details are read when the card is scanned in the Deck's card reader",
+                ))
+                .padding(4)
+                .into(),
+            );
+        }
+
+        for (label, value) in details {
+            element_list.push(
+                row![
+                    container(text(label).size(25)).width(Length::Units(180)),
+                    text(value).size(25),
+                ]
+                .padding(4)
+                .into(),
+            );
+        }
+
+        element_list.push(
+            row![
+                focus.text_field(
+                    text_input(
+                        "Where and when it was bought...",
+                        &app.purchase_note_draft,
+                        Message::PurchaseNoteInput
+                    )
+                    .on_submit(Message::SavePurchaseNote)
+                    .size(25)
+                    .padding(6)
+                    .width(Length::Units(500)),
+                    KeyboardTarget::PurchaseNote,
+                ),
+                focus
+                    .button(text("Save Note").size(25), Message::SavePurchaseNote)
+                    .padding(8),
+            ]
+            .spacing(10)
+            .padding(4)
+            .into(),
+        );
+
+        if let Some(status) = &app.card_info_status {
+            element_list.push(container(text(status)).padding(4).into());
+        }
+
+        element_list.push(
+            row![
+                focus
+                    .button(
+                        text("Free Up Space").size(25),
+                        Message::ShowUninstall(card.uuid.clone()),
+                    )
+                    .padding(8),
+                focus
+                    .button(
+                        text("Add Games to Steam").size(25),
+                        Message::ShowShortcuts(card.uuid.clone()),
+                    )
+                    .padding(8),
+            ]
+            .spacing(10)
+            .padding(4)
+            .into(),
+        );
+
+        element_list.push(container(text("Game Files").size(30)).padding(4).into());
+        if app.verify_running.is_some() {
+            element_list.push(
+                container(text("Verifying, this can take a while...").size(25))
+                    .padding(4)
+                    .into(),
+            );
+        } else {
+            element_list.push(
+                container(
+                    focus
+                        .button(
+                            text("Verify Games").size(25),
+                            Message::VerifyCard(card.uuid.clone()),
+                        )
+                        .padding(8),
+                )
+                .padding(4)
+                .into(),
+            );
+        }
+
+        for library in [Library::Steam, Library::Lutris, Library::Heroic] {
+            for game in card
+                .library_games(library)
+                .iter()
+                .filter(|game| !game.problems.is_empty())
+            {
+                element_list.push(
+                    column![
+                        text(&game.name).size(25),
+                        text(game.problems.join("\n")).size(20),
+                    ]
+                    .padding(4)
+                    .into(),
+                );
+            }
+        }
+
+        element_list.push(container(text("Speed Tests").size(30)).padding(4).into());
+        if app.benchmark_running.is_some() {
+            element_list.push(
+                container(text("Testing, this takes a minute...").size(25))
+                    .padding(4)
+                    .into(),
+            );
+        } else {
+            element_list.push(
+                row![
+                    focus
+                        .button(
+                            text("Test Read Speed").size(25),
+                            Message::RunBenchmark(card.uuid.clone(), false),
+                        )
+                        .padding(8),
+                    focus
+                        .button(
+                            text("Test Read and Write Speed").size(25),
+                            Message::RunBenchmark(card.uuid.clone(), true),
+                        )
+                        .padding(8),
+                ]
+                .spacing(10)
+                .padding(4)
+                .into(),
+            );
+        }
+
+        // Newest first
+        for result in card.benchmarks.iter().rev() {
+            element_list.push(
+                row![
+                    container(text(scanning::format_date(result.timestamp)).size(20))
+                        .width(Length::Units(180)),
+                    text(result.summary()).size(20),
+                ]
+                .padding(4)
+                .into(),
+            );
+        }
+
+        scrollable(column(element_list))
+    }
+
+    fn shortcuts(app: &'a App, focus: &mut Focusables) -> Scrollable<'a, Message> {
+        let selection = match &app.shortcut_selection {
+            Some(selection) => selection,
+            None => return scrollable(column![text("No card picked").size(40)]),
+        };
+
+        let mut element_list: Vec<Element<Message>> = vec![
+            container(
+                text(format!(
+                    "Add Games on {} to Steam",
+                    app.card_name(&selection.card_uuid)
+                ))
+                .size(50),
+            )
+            .padding(4)
+            .into(),
+            container(
+                text("Adds the picked Lutris and Heroic games to Steam as non-Steam games, Steam has to be restarted before they show up")
+                    .size(20),
+            )
+            .padding(4)
+            .into(),
+        ];
+
+        let candidates = match &selection.candidates {
+            Some(candidates) => candidates,
+            None => {
+                element_list.push(
+                    container(text("Looking for the games' executables...").size(25))
+                        .padding(4)
+                        .into(),
+                );
+                return scrollable(column(element_list));
+            }
+        };
+
+        if !selection.running && !candidates.is_empty() {
+            let add_button = if selection.selected.is_empty() {
+                button(text("Add to Steam").size(25))
+            } else {
+                focus.button(text("Add to Steam").size(25), Message::AddShortcuts)
+            };
+            element_list.push(
+                row![
+                    text(format!("{} selected", selection.selected.len())).size(25),
+                    add_button.padding(8)
+                ]
+                .spacing(10)
+                .padding(4)
+                .align_items(Alignment::Center)
+                .into(),
+            );
+        }
+
+        if let Some(status) = &selection.status {
+            element_list.push(container(text(status)).padding(4).into());
+        }
+        if candidates.is_empty() && selection.status.is_none() {
+            element_list.push(
+                container(text("No Lutris or Heroic games on this card").size(25))
+                    .padding(4)
+                    .into(),
+            );
+        }
+
+        for (index, candidate) in candidates.iter().enumerate() {
+            let (library_label, library_color, _) = utils::library_style(candidate.library);
+            let select: Element<Message> = if candidate.in_steam {
+                container(text("In Steam").size(20))
+                    .width(Length::Units(120))
+                    .padding(8)
+                    .into()
+            } else {
+                focus
+                    .button(
+                        text(if selection.selected.contains(&index) {
+                            "Selected"
+                        } else {
+                            "Select"
+                        })
+                        .size(20),
+                        Message::ToggleShortcutGame(index),
+                    )
+                    .width(Length::Units(120))
+                    .padding(8)
+                    .into()
+            };
+            let launch_label = match &candidate.launch {
+                Launch::Launcher => format!("Through {library_label}"),
+                Launch::Executable(path) => format!(
+                    "Run {}",
+                    path.file_name().unwrap_or_default().to_string_lossy()
+                ),
+            };
+            // Only games with an executable found can switch how they're started
+            let launch: Element<Message> = if candidate.executable.is_some() {
+                focus
+                    .button(
+                        text(launch_label).size(20),
+                        Message::ToggleShortcutLaunch(index),
+                    )
+                    .padding(8)
+                    .into()
+            } else {
+                text(launch_label).size(20).into()
+            };
+
+            element_list.push(
+                row![
+                    select,
+                    container(text(&candidate.game).size(25)).width(Length::Fill),
+                    container(text(library_label).style(library_color).size(20))
+                        .width(Length::Units(100)),
+                    launch,
+                ]
+                .spacing(10)
+                .padding(4)
+                .align_items(Alignment::Center)
+                .into(),
+            );
+            if let Some(warning) = candidate.proton_warning() {
+                element_list.push(container(text(warning).size(18)).padding(4).into());
+            }
+        }
+
+        scrollable(column(element_list))
+    }
+
+    fn uninstall(app: &'a App, focus: &mut Focusables) -> Scrollable<'a, Message> {
+        let selection = match &app.uninstall {
+            Some(selection) => selection,
+            None => return scrollable(column![text("No card picked").size(40)]),
+        };
+
+        let mut element_list: Vec<Element<Message>> = vec![container(
+            text(format!(
+                "Free Up Space on {}",
+                app.card_name(&selection.card_uuid)
+            ))
+            .size(50),
+        )
+        .padding(4)
+        .into()];
+
+        let removals = match &selection.removals {
+            Some(removals) => removals,
+            None => {
+                element_list.push(
+                    container(text("Adding up the game sizes...").size(25))
+                        .padding(4)
+                        .into(),
+                );
+                return scrollable(column(element_list));
+            }
+        };
+
+        let selected = selection.selected_removals();
+        let freed: u64 = selected.iter().map(|removal| removal.bytes).sum();
+        let summary = format!(
+            "{} selected, frees {}",
+            selected.len(),
+            scanning::format_size(freed)
+        );
+
+        if selection.confirming {
+            element_list.push(
+                row![
+                    text(format!("Uninstall {summary}? This can't be undone")).size(22),
+                    focus.button(text("Uninstall"), Message::ConfirmUninstall),
+                    focus.button(text("Cancel"), Message::CancelUninstall),
+                ]
+                .spacing(10)
+                .padding(4)
+                .align_items(Alignment::Center)
+                .into(),
+            );
+            for warning in selected
+                .iter()
+                .filter_map(|removal| removal.launcher_warning())
+            {
+                element_list.push(container(text(warning).size(20)).padding(4).into());
+            }
+            if selected
+                .iter()
+                .any(|removal| removal.library == Library::Steam)
+            {
+                element_list.push(
+                    container(
+                        text("The Steam games' Proton prefixes and the saves in them are kept, they're listed under Proton Prefixes afterwards")
+                            .size(20),
+                    )
+                    .padding(4)
+                    .into(),
+                );
+            }
+        } else if !selection.running {
+            let uninstall_button = if selected.is_empty() {
+                button(text("Uninstall").size(25))
+            } else {
+                focus.button(text("Uninstall").size(25), Message::RequestUninstall)
+            };
+            element_list.push(
+                row![text(summary).size(25), uninstall_button.padding(8)]
+                    .spacing(10)
+                    .padding(4)
+                    .align_items(Alignment::Center)
+                    .into(),
+            );
+        }
+
+        if let Some(status) = &selection.status {
+            element_list.push(container(text(status)).padding(4).into());
+        }
+
+        for (index, removal) in removals.iter().enumerate() {
+            let (library_label, library_color, _) = utils::library_style(removal.library);
+            element_list.push(
+                row![
+                    focus
+                        .button(
+                            text(if selection.selected.contains(&index) {
+                                "Selected"
+                            } else {
+                                "Select"
+                            })
+                            .size(20),
+                            Message::ToggleUninstallGame(index),
+                        )
+                        .width(Length::Units(120))
+                        .padding(8),
+                    container(text(&removal.game).size(25)).width(Length::Fill),
+                    container(text(library_label).style(library_color).size(20))
+                        .width(Length::Units(100)),
+                    container(text(scanning::format_size(removal.bytes)).size(25))
+                        .width(Length::Units(140)),
+                ]
+                .spacing(10)
+                .padding(4)
+                .align_items(Alignment::Center)
+                .into(),
+            );
+        }
+
+        element_list.push(
+            container(text("Leftover Folders").size(30))
+                .padding(4)
+                .into(),
+        );
+        element_list.push(
+            container(
+                text("Shader caches and unfinished downloads of games that aren't on the card anymore")
+                    .size(20),
+            )
+            .padding(4)
+            .into(),
+        );
+        match &selection.leftovers {
+            None if selection.running => {}
+            None => element_list.push(
+                container(text("Looking for leftover folders...").size(25))
+                    .padding(4)
+                    .into(),
+            ),
+            Some(leftovers) => {
+                let (prefixes, others): (Vec<&Leftover>, Vec<&Leftover>) = leftovers
+                    .iter()
+                    .partition(|leftover| leftover.kind.holds_saves());
+
+                Self::leftover_group(app, focus, &others, false, &mut element_list);
+
+                element_list.push(
+                    container(text("Proton Prefixes").size(30))
+                        .padding(4)
+                        .into(),
+                );
+                element_list.push(
+                    container(
+                        text("The compatdata folders of games without an appmanifest on this card. They hold the saves of Windows games, \
+                            including games installed on another card or the internal drive and games you might install again. \
+                            Removing them deletes those saves unless Steam Cloud has a copy")
+                            .size(20)
+                            .style(theming::ERROR_COLOR),
+                    )
+                    .padding(4)
+                    .into(),
+                );
+                Self::leftover_group(app, focus, &prefixes, true, &mut element_list);
+            }
+        }
+
+        scrollable(column(element_list))
+    }
+
+    /// The leftovers of one kind with the button to remove them, `prefixes` is set for the Proton prefixes
+    /// which are confirmed on their own
+    fn leftover_group(
+        app: &'a App,
+        focus: &mut Focusables,
+        leftovers: &[&Leftover],
+        prefixes: bool,
+        element_list: &mut Vec<Element<'a, Message>>,
+    ) {
+        let selection = match &app.uninstall {
+            Some(selection) => selection,
+            None => return,
+        };
+        if leftovers.is_empty() {
+            element_list.push(container(text("None found").size(25)).padding(4).into());
+            return;
+        }
+
+        let total: u64 = leftovers.iter().map(|leftover| leftover.bytes).sum();
+        let confirming = if prefixes {
+            selection.confirming_prefixes
+        } else {
+            selection.confirming_leftovers
+        };
+        if confirming {
+            let question = if prefixes {
+                format!(
+                    "Delete {} Proton prefixes and the game saves in them, freeing {}? This can't be undone",
+                    leftovers.len(),
+                    scanning::format_size(total)
+                )
+            } else {
+                format!(
+                    "Remove {} leftover folders and free {}?",
+                    leftovers.len(),
+                    scanning::format_size(total)
+                )
+            };
+            element_list.push(
+                row![
+                    text(question).size(22),
+                    focus.button(text("Remove"), Message::ConfirmRemoveLeftovers(prefixes)),
+                    focus.button(text("Cancel"), Message::CancelUninstall),
+                ]
+                .spacing(10)
+                .padding(4)
+                .align_items(Alignment::Center)
+                .into(),
+            );
+        } else if !selection.running {
+            let label = if prefixes {
+                format!(
+                    "Delete Prefixes and Saves, frees {}",
+                    scanning::format_size(total)
+                )
+            } else {
+                format!("Remove All, frees {}", scanning::format_size(total))
+            };
+            element_list.push(
+                container(
+                    focus
+                        .button(
+                            text(label).size(25),
+                            Message::RequestRemoveLeftovers(prefixes),
+                        )
+                        .padding(8),
+                )
+                .padding(4)
+                .into(),
+            );
+        }
+
+        for leftover in leftovers {
+            let name = match cleanup::game_name(&app.card_data, leftover.appid) {
+                Some(game) => format!("{} ({game})", leftover.name()),
+                None => leftover.name(),
+            };
+            element_list.push(
+                row![
+                    container(text(name).size(25)).width(Length::Fill),
+                    container(text(scanning::format_size(leftover.bytes)).size(25))
+                        .width(Length::Units(140)),
+                ]
+                .spacing(10)
+                .padding(4)
+                .into(),
+            );
+        }
+    }
+
+    fn planner(app: &'a App, focus: &mut Focusables) -> Scrollable<'a, Message> {
+        let mut element_list: Vec<Element<Message>> = vec![
+            container(text("Space Planner").size(50)).padding(4).into(),
+            row![
+                focus.text_field(
+                    text_input(
+                        "Game size, e.g. 45G, or a Steam app ID",
+                        &app.planner_input,
+                        Message::PlannerInput
+                    )
+                    .on_submit(Message::RunPlanner)
+                    .size(25)
+                    .padding(6)
+                    .width(Length::Units(500)),
+                    KeyboardTarget::PlannerSize,
+                ),
+                focus
+                    .button(text("Find a Card").size(25), Message::RunPlanner)
+                    .padding(8),
+            ]
+            .spacing(10)
+            .padding(4)
+            .into(),
+        ];
+
+        let (requirement, placements) = match &app.planner_result {
+            Some(Ok(result)) => result,
+            Some(Err(problem)) => {
+                element_list.push(container(text(problem)).padding(4).into());
+                return scrollable(column(element_list));
+            }
+            None => return scrollable(column(element_list)),
+        };
+
+        let needed = scanning::format_size(requirement.bytes);
+        element_list.push(
+            container(
+                text(match &requirement.game {
+                    Some(game) => format!("{game} needs {needed}"),
+                    None => format!("The game needs {needed}"),
+                })
+                .size(25),
+            )
+            .padding(4)
+            .into(),
+        );
+
+        let any_fits = placements
+            .iter()
+            .any(|placement| placement.fits(requirement.bytes));
+        if !any_fits {
+            element_list.push(
+                container(
+                    text("It doesn't fit on any card, removing these games would make room:")
+                        .size(25),
+                )
+                .padding(4)
+                .into(),
+            );
+        }
+
+        for placement in placements {
+            let free = match placement.free {
+                Some(free) if placement.fits(requirement.bytes) => format!(
+                    "{} free, {} left after",
+                    scanning::format_size(free),
+                    scanning::format_size(free - requirement.bytes)
+                ),
+                Some(free) => format!(
+                    "{} free, {} short",
+                    scanning::format_size(free),
+                    scanning::format_size(requirement.bytes - free)
+                ),
+                None => String::from("Free space unknown, rescan the card"),
+            };
+            element_list.push(
+                row![
+                    container(focus.text_button(
+                        text(&placement.card_name).size(25),
+                        Message::ShowCardInfo(placement.card_uuid.clone()),
+                    ))
+                    .width(Length::Units(300)),
+                    container(text(free).size(25)).width(Length::Fill),
+                    text(if placement.connected {
+                        "Inserted"
+                    } else {
+                        "Not inserted"
+                    })
+                    .size(20),
+                ]
+                .spacing(10)
+                .padding(4)
+                .align_items(Alignment::Center)
+                .into(),
+            );
+
+            if any_fits {
+                continue;
+            }
+            for candidate in &placement.candidates {
+                let (_, library_color, _) = utils::library_style(candidate.library);
+                element_list.push(
+                    row![
+                        container(text(&candidate.game).style(library_color).size(20))
+                            .width(Length::Units(300)),
+                        container(text(scanning::format_size(candidate.size)).size(20))
+                            .width(Length::Units(120)),
+                        text(candidate.reasons.join(", ")).size(20),
+                    ]
+                    .spacing(10)
+                    .padding([0, 4, 0, 40])
+                    .into(),
+                );
+            }
+        }
+
+        scrollable(column(element_list))
+    }
+
+    fn steam_library(app: &'a App, focus: &mut Focusables) -> Scrollable<'a, Message> {
+        let mut element_list: Vec<Element<Message>> = vec![
+            container(text("Steam Library").size(50)).padding(4).into(),
+            container(
+                text(
+                    "Games installed or played on this Deck, and which card to insert to play them",
+                )
+                .size(20),
+            )
+            .padding(4)
+            .into(),
+            container(
+                focus
+                    .button(
+                        text(if app.other_cards_only {
+                            "Show All Games"
+                        } else {
+                            "Only Games on Other Cards"
+                        })
+                        .size(25),
+                        Message::ToggleOtherCardsOnly,
+                    )
+                    .padding(8),
+            )
+            .padding(4)
+            .into(),
+        ];
+
+        let entries = match &app.steam_library {
+            Some(Ok(entries)) => entries,
+            Some(Err(problem)) => {
+                element_list.push(container(text(problem)).padding(4).into());
+                return scrollable(column(element_list));
+            }
+            None => {
+                element_list.push(
+                    container(text("Reading Steam's library...").size(25))
+                        .padding(4)
+                        .into(),
+                );
+                return scrollable(column(element_list));
+            }
+        };
+
+        for entry in entries
+            .iter()
+            .filter(|entry| !app.other_cards_only || entry.only_on_other_cards())
+        {
+            let location = if entry.locations.is_empty() {
+                String::from("Not installed")
+            } else {
+                entry
+                    .locations
+                    .iter()
+                    .map(|location| match location {
+                        Location::Internal => String::from("This Deck"),
+                        Location::Card {
+                            card_name,
+                            inserted: true,
+                            ..
+                        } => format!("{card_name} (inserted)"),
+                        Location::Card { card_name, .. } => card_name.clone(),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            };
+            let location = if entry.only_on_other_cards() {
+                text(format!("Insert {location}"))
+                    .style(theming::HIGHLIGHT_COLOR)
+                    .size(20)
+            } else {
+                text(location).size(20)
+            };
+
+            element_list.push(
+                row![
+                    container(text(&entry.name).size(25)).width(Length::FillPortion(2)),
+                    container(location).width(Length::FillPortion(2)),
+                    container(
+                        text(
+                            entry.last_played.map_or_else(
+                                || String::from("Never played"),
+                                scanning::format_date
+                            )
+                        )
+                        .size(20)
+                    )
+                    .width(Length::Units(140)),
+                ]
+                .spacing(10)
+                .padding(4)
+                .align_items(Alignment::Center)
+                .into(),
+            );
+        }
+
+        scrollable(column(element_list))
+    }
+
+    fn game(app: &'a App, focus: &mut Focusables) -> Column<'a, Message> {
+        let found = app
+            .selected_game
+            .as_ref()
+            .and_then(|game_id| Some((game_id, utils::find_game(&app.card_data, game_id)?)));
+
+        let (game_id, card, game) = match found {
+            Some((game_id, (card, game))) => (game_id, card, game),
+            None => {
+                return column![
+                    text("This game is no longer on the list").size(40),
+                    text(app.game_status.as_deref().unwrap_or_default()),
+                ]
+            }
+        };
+
+        let path = card.game_path(game_id.library, game);
+        let (library_label, library_color, _) = utils::library_style(game_id.library);
+
+        let other_cards = utils::other_cards_with_game(&app.card_data, &card.uuid, game);
+        let other_cards = if other_cards.is_empty() {
+            String::from("None")
+        } else {
+            other_cards
+                .iter()
+                .map(|card| card.name.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        };
+
+        let unknown = || String::from("Unknown");
+        let details = [
+            (
+                "Path",
+                path.as_ref()
+                    .map_or_else(unknown, |path| path.display().to_string()),
+            ),
+            ("Card", card.name.clone()),
+            (
+                "Size",
+                game.size.map_or_else(unknown, scanning::format_size),
+            ),
+            (
+                "App ID",
+                game.appid.map_or_else(unknown, |appid| appid.to_string()),
+            ),
+            (
+                "Build",
+                game.build.map_or_else(unknown, |build| build.to_string()),
+            ),
+            (
+                "Install State",
+                game.state_flags
+                    .map_or_else(unknown, |flags| scanning::install_state(flags).to_string()),
+            ),
+            (
+                "Last Played",
+                game.last_played
+                    .map_or_else(|| String::from("Never"), scanning::format_date),
+            ),
+            (
+                "Playtime",
+                game.playtime
+                    .map_or_else(unknown, scanning::format_playtime),
+            ),
+            ("Also On", other_cards),
+        ];
+
+        let mut element_list: Vec<Element<Message>> = vec![
+            container(text(&game.name).size(50)).padding(4).into(),
+            container(
+                text(format!("{library_label} Library"))
+                    .style(library_color)
+                    .size(30),
+            )
+            .padding(4)
+            .into(),
+        ];
+
+        for (label, value) in details {
+            element_list.push(
+                row![
+                    container(text(label).size(25)).width(Length::Units(180)),
+                    text(value).size(25),
+                ]
+                .padding(4)
+                .into(),
+            );
+        }
+
+        if !game.problems.is_empty() {
+            element_list.push(
+                column![
+                    text("Problems found by the last verify")
+                        .size(25)
+                        .style(theming::ERROR_COLOR),
+                    text(game.problems.join("\n")).size(20),
+                ]
+                .padding(4)
+                .into(),
+            );
+        }
+
+        if let Some(changes) = &app.game_changes {
+            element_list.push(
+                container(
+                    text(format!(
+                        "Changed between the scans on {} and {}",
+                        scanning::format_date(changes.since),
+                        scanning::format_date(changes.found)
+                    ))
+                    .size(25),
+                )
+                .padding(4)
+                .into(),
+            );
+            for (label, paths) in [
+                ("Added", &changes.added),
+                ("Removed", &changes.removed),
+                ("Modified", &changes.modified),
+            ] {
+                if paths.is_empty() {
+                    continue;
+                }
+                let mut listed: Vec<&str> = paths
+                    .iter()
+                    .take(MAX_LISTED_CHANGES)
+                    .map(|path| path.as_str())
+                    .collect();
+                let more = format!(
+                    "...and {} more",
+                    paths.len().saturating_sub(MAX_LISTED_CHANGES)
+                );
+                if paths.len() > MAX_LISTED_CHANGES {
+                    listed.push(&more);
+                }
+                element_list.push(
+                    column![
+                        text(format!("{label} ({})", paths.len())).size(20),
+                        text(listed.join("\n")).size(16),
+                    ]
+                    .padding(4)
+                    .into(),
+                );
+            }
+        }
+
+        // The folder can only be opened while the card is inserted
+        let open_folder = if path.as_ref().is_some_and(|path| path.is_dir()) {
+            focus.button(text("Open Folder").size(25), Message::OpenGameFolder)
+        } else {
+            button(text("Open Folder").size(25))
+        };
+        let copy_path = if path.is_some() {
+            focus.button(text("Copy Path").size(25), Message::CopyGamePath)
+        } else {
+            button(text("Copy Path").size(25))
+        };
+
+        element_list.push(
+            row![
+                open_folder.padding(8),
+                copy_path.padding(8),
+                focus
+                    .button(
+                        text(if game.favourite {
+                            "Unfavourite"
+                        } else {
+                            "Favourite"
+                        })
+                        .size(25),
+                        Message::ToggleFavourite,
+                    )
+                    .padding(8),
+                focus
+                    .button(
+                        text("Find a Card for It").size(25),
+                        Message::PlanGame(game_id.clone()),
+                    )
+                    .padding(8),
+            ]
+            .spacing(10)
+            .padding(4)
+            .into(),
+        );
+
+        element_list.push(
+            row![
+                focus.text_field(
+                    text_input("Add a note...", &app.note_draft, Message::NoteInput)
+                        .on_submit(Message::SaveNote)
+                        .size(25)
+                        .padding(6)
+                        .width(Length::Units(500)),
+                    KeyboardTarget::Note,
+                ),
+                focus
+                    .button(text("Save Note").size(25), Message::SaveNote)
+                    .padding(8),
+            ]
+            .spacing(10)
+            .padding(4)
+            .into(),
+        );
+
+        if game_id.library == Library::Steam {
+            element_list.push(
+                container(text("Copy or Move to Another Library").size(30))
+                    .padding(4)
+                    .into(),
+            );
+            if let Some(active) = &app.transfer {
+                let progress = active.progress.map_or_else(
+                    || String::from("Starting..."),
+                    |progress| progress.summary(),
+                );
+                element_list.push(
+                    container(text(format!("{}: {progress}", active.target.name)).size(25))
+                        .padding(4)
+                        .into(),
+                );
+            } else if app.transfer_targets.is_empty() {
+                element_list.push(
+                    container(text("No other cards or Steam libraries found").size(20))
+                        .padding(4)
+                        .into(),
+                );
+            }
+            if app.transfer.is_none() {
+                for (index, target) in app.transfer_targets.iter().enumerate() {
+                    element_list.push(
+                        row![
+                            container(text(&target.name).size(25)).width(Length::Units(300)),
+                            focus
+                                .button(text("Copy").size(25), Message::StartTransfer(index, false))
+                                .padding(8),
+                            focus
+                                .button(text("Move").size(25), Message::StartTransfer(index, true))
+                                .padding(8),
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center)
+                        .padding(4)
+                        .into(),
+                    );
+                }
+            }
+        }
+
+        if let Some(status) = &app.game_status {
+            element_list.push(container(text(status)).padding(4).into());
+        }
+
         column(element_list)
     }
+
+    fn error_log(app: &'a App, focus: &mut Focusables) -> Scrollable<'a, Message> {
+        let mut element_list: Vec<Element<Message>> = vec![
+            container(text("Error Log").size(50)).padding(4).into(),
+            container(
+                text("Problems found while scanning and saving since the app was opened, newest first")
+                    .size(20),
+            )
+            .padding(4)
+            .into(),
+        ];
+
+        if app.error_log.is_empty() {
+            element_list.push(
+                container(text("No problems so far").size(25))
+                    .padding(4)
+                    .into(),
+            );
+            return scrollable(column(element_list));
+        }
+
+        element_list.push(
+            container(
+                focus
+                    .button(text("Clear Log").size(25), Message::ClearErrorLog)
+                    .padding(8),
+            )
+            .padding(4)
+            .into(),
+        );
+
+        for entry in app.error_log.iter().rev() {
+            element_list.push(
+                row![
+                    container(text(scanning::format_date(entry.time)).size(20))
+                        .width(Length::Units(140)),
+                    text(&entry.message).size(20).width(Length::Fill),
+                ]
+                .spacing(10)
+                .padding(4)
+                .into(),
+            );
+        }
+
+        scrollable(column(element_list))
+    }
 }
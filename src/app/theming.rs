@@ -1,5 +1,5 @@
-use iced::theme::Theme;
-use iced::widget::container;
+use iced::theme::{self, Theme};
+use iced::widget::{button, container};
 use iced::Color;
 
 // steam green 4d5a45 - ugly
@@ -39,6 +39,24 @@ pub static HEROIC_COLOR: Color = Color {
     b: 0xed as f32 / 255.0,
 };
 
+// yellow used to highlight the parts of a game's name that matched the search
+
+pub static HIGHLIGHT_COLOR: Color = Color {
+    a: 1.0,
+    r: 0xff as f32 / 255.0,
+    g: 0xd5 as f32 / 255.0,
+    b: 0x4f as f32 / 255.0,
+};
+
+// red used to mark games that failed a verify
+
+pub static ERROR_COLOR: Color = Color {
+    a: 1.0,
+    r: 0xef as f32 / 255.0,
+    g: 0x53 as f32 / 255.0,
+    b: 0x50 as f32 / 255.0,
+};
+
 pub static LABEL_BORDER_COLOR: Color = Color {
     a: 1.0,
     r: 0x9c as f32 / 255.0,
@@ -72,6 +90,71 @@ pub static SETTINGS_LABEL_CONTAINER_STYLE: fn(&Theme) -> container::Appearance =
         ..Default::default()
     };
 
+/// The box around a problem shown above the page
+pub static TOAST_CONTAINER_STYLE: fn(&Theme) -> container::Appearance =
+    |_theme| container::Appearance {
+        border_color: ERROR_COLOR,
+        border_width: 2.0,
+        border_radius: 4.0,
+        ..Default::default()
+    };
+
+/// The ring around the text field the controller is focused on, the same as `FocusRing`'s
+pub static FOCUSED_FIELD_CONTAINER_STYLE: fn(&Theme) -> container::Appearance =
+    |_theme| container::Appearance {
+        border_color: HIGHLIGHT_COLOR,
+        border_width: 3.0,
+        border_radius: 4.0,
+        ..Default::default()
+    };
+
+/// Draws a border around the button the controller is focused on, on top of its normal look
+pub struct FocusRing {
+    /// Whether the button normally uses the plain Text style, like the games in the list
+    pub text_button: bool,
+}
+
+impl FocusRing {
+    fn base_style(&self) -> theme::Button {
+        if self.text_button {
+            theme::Button::Text
+        } else {
+            theme::Button::Primary
+        }
+    }
+}
+
+impl button::StyleSheet for FocusRing {
+    type Style = Theme;
+
+    fn active(&self, style: &Self::Style) -> button::Appearance {
+        button::Appearance {
+            border_color: HIGHLIGHT_COLOR,
+            border_width: 3.0,
+            border_radius: 4.0,
+            ..style.active(&self.base_style())
+        }
+    }
+
+    fn hovered(&self, style: &Self::Style) -> button::Appearance {
+        button::Appearance {
+            border_color: HIGHLIGHT_COLOR,
+            border_width: 3.0,
+            border_radius: 4.0,
+            ..style.hovered(&self.base_style())
+        }
+    }
+}
+
+/// The button style to use depending on whether the controller is focused on it
+pub fn focus_style(focused: bool, text_button: bool) -> theme::Button {
+    match (focused, text_button) {
+        (true, _) => theme::Button::Custom(Box::new(FocusRing { text_button })),
+        (false, true) => theme::Button::Text,
+        (false, false) => theme::Button::Primary,
+    }
+}
+
 // I would like to organize the different styles and themes into an enum to help express the structure
 // Can't get the different impl and attempts to work
 
@@ -0,0 +1,53 @@
+use crate::app::Message;
+use iced::futures::channel::mpsc;
+use iced::futures::StreamExt;
+use iced::{subscription, Subscription};
+use sdscanner::transfer::{self, Progress, Transfer};
+use sdscanner::Error;
+use std::thread;
+
+enum Update {
+    Progress(Progress),
+    Finished(Result<(), Error>),
+}
+
+enum State {
+    Starting(Transfer),
+    Running(mpsc::UnboundedReceiver<Update>),
+    Finished,
+}
+
+/// Runs the transfer on a background thread, sending `Message::TransferProgress` as it goes
+/// and `Message::TransferFinished` at the end. Only used while a transfer is running
+pub fn subscription(transfer: Transfer) -> Subscription<Message> {
+    subscription::unfold(
+        transfer.clone(),
+        State::Starting(transfer),
+        |state| async move {
+            match state {
+                State::Starting(transfer) => {
+                    let (sender, receiver) = mpsc::unbounded();
+                    thread::spawn(move || {
+                        let result = transfer::run(&transfer, |progress| {
+                            let _ = sender.unbounded_send(Update::Progress(progress));
+                        });
+                        let _ = sender.unbounded_send(Update::Finished(result));
+                    });
+
+                    (None, State::Running(receiver))
+                }
+                State::Running(mut receiver) => match receiver.next().await {
+                    Some(Update::Progress(progress)) => (
+                        Some(Message::TransferProgress(progress)),
+                        State::Running(receiver),
+                    ),
+                    Some(Update::Finished(result)) => {
+                        (Some(Message::TransferFinished(result)), State::Finished)
+                    }
+                    None => (None, State::Finished),
+                },
+                State::Finished => iced::futures::future::pending().await,
+            }
+        },
+    )
+}
@@ -1,253 +1,633 @@
+use crate::app::keyboard::{KeyboardField, KeyboardTarget};
+use crate::app::pages::{ListView, SortBy};
 use crate::app::theming;
-use crate::app::Message;
-use crate::scanning::{self, save_data_to_json, Card, Game, OtherLibrary};
-use iced::widget::{button, column, container, scrollable, text, Column, Scrollable};
-use iced::{Element, Length};
-
-/// Returns a copy of the passed in list after it's been filtered by the search term
-pub fn filter_list(list: &Vec<Card>, search_term: &str) -> Vec<Card> {
-    list.into_iter()
+use crate::app::{GameId, Message};
+use iced::widget::{
+    button, column, container, image, row, scrollable, text, Button, Column, Row, Scrollable,
+};
+use iced::{Alignment, Color, Element, Length, Theme};
+use sdscanner::artwork;
+use sdscanner::identity;
+use sdscanner::scanning::{self, Card, Game, Library, OtherLibrary, ScanData};
+use sdscanner::search::Query;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+/// Returns a copy of the passed in list after it's been filtered by the search query,
+/// each library's games are sorted with the best matches first
+pub fn filter_list(list: &[Card], query: &Query) -> Vec<Card> {
+    list.iter()
+        .filter(|card| query.matches_card(card))
         .map(|card| {
-            // Use map to change the contents of each SD card's list by filter by the given search term
+            // Use map to change the contents of each SD card's list by filter by the given search query
             Card {
-                games: card
-                    .games
-                    .clone()
-                    .into_iter()
-                    .filter(|game| {
-                        game.name
-                            .to_ascii_lowercase()
-                            .contains(&search_term.to_ascii_lowercase())
-                    })
-                    .collect(),
-                name: card.name.clone(),
-                uuid: card.uuid.clone(),
-                heroic: if let Some(heroic) = card.heroic.clone() {
-                    Some(OtherLibrary {
-                        games: heroic
-                            .games
-                            .into_iter()
-                            .filter(|game| {
-                                game.name
-                                    .to_ascii_lowercase()
-                                    .contains(&search_term.to_ascii_lowercase())
-                            })
-                            .collect(),
-                        path: heroic.path.clone(),
-                    })
-                } else {
-                    None
-                },
-                lutris: if let Some(lutris) = card.lutris.clone() {
-                    Some(OtherLibrary {
-                        games: lutris
-                            .games
-                            .into_iter()
-                            .filter(|game| {
-                                game.name
-                                    .to_ascii_lowercase()
-                                    .contains(&search_term.to_ascii_lowercase())
-                            })
-                            .collect(),
-                        path: lutris.path.clone(),
-                    })
-                } else {
-                    None
-                },
+                games: query.filter_games(&card.games, Library::Steam),
+                heroic: card.heroic.as_ref().map(|heroic| OtherLibrary {
+                    games: query.filter_games(&heroic.games, Library::Heroic),
+                    path: heroic.path.clone(),
+                }),
+                lutris: card.lutris.as_ref().map(|lutris| OtherLibrary {
+                    games: query.filter_games(&lutris.games, Library::Lutris),
+                    path: lutris.path.clone(),
+                }),
+                ..card.clone()
             }
         })
         .collect()
 }
 
 /// TODO needs to return possibly multiple names for multiple inserted cards
-/// Returns the name of the currently inserted card, returns None if there is no inserted card or there was an issue getting the name.
-pub fn get_card_name(list: &Vec<Card>) -> Option<String> {
-    let mut inserted_cards = scanning::get_card_info()?;
+/// Returns the name of the currently inserted card, returns None if there is no inserted card or it isn't in the list.
+pub fn get_card_name(list: &[Card], inserted_cards: &[ScanData]) -> Option<String> {
+    // Find the card in the list that matches the inserted card
+    let scanned_card = inserted_cards.first()?;
+    identity::find_card(list, scanned_card).map(|index| list[index].name.clone())
+}
 
-    if inserted_cards.len() == 0 {
-        // If no cards were scanned, then return None
-        return None;
-    }
+/// How the list page is currently set up
+pub struct ListOptions<'a> {
+    /// The text in the search bar
+    pub search_term: &'a str,
+    pub list_view: ListView,
+    pub sort_by: SortBy,
+    /// UUIDs of the cards whose games are hidden
+    pub collapsed_cards: &'a HashSet<String>,
+    /// The cover art loaded so far for the grid, keyed by app ID
+    pub artwork: &'a HashMap<u32, image::Handle>,
+}
 
-    for scanned_card in &mut inserted_cards {
-        scanned_card.name = list
-            .iter()
-            .filter(|&card| card.uuid == scanned_card.uuid)
-            // Filter for the card in the list with the same uuid as the inserted card
-            .map(|card| card.name.clone())
-            .next();
-    }
+/// How many games are in each row of the grid
+const GRID_COLUMNS: usize = 6;
 
-    if inserted_cards.len() >= 1 {
-        inserted_cards[0].name.clone()
-    } else {
-        None
-    }
+/// The ID of the list page's scrollable, used to scroll it along with the controller focus
+pub fn list_scrollable_id() -> scrollable::Id {
+    scrollable::Id::new("game-list")
 }
 
 /// Converts the list data into an Iced GUI list of the cards and their games
 /// Also provides the search functionality by filtering the list data by the `search_term`
 /// The `search_term` is is provided by the user in search bar
 pub fn create_card_and_games_list<'a>(
-    list: &'a Vec<Card>,
-    search_term: &'a str,
+    list: &'a [Card],
+    options: &ListOptions,
+    focus: &mut Focusables,
 ) -> Scrollable<'a, Message> {
-    let mut return_list: Vec<Element<Message>> = vec![];
+    let query = Query::parse(options.search_term);
 
-    let list = if search_term.is_empty() {
-        // If the search term is empty, don't filter the list
-        list.clone()
+    let mut list = if query.is_empty() {
+        // If the search term is empty, don't filter the list, archived cards only show up in searches
+        list.iter().filter(|card| !card.archived).cloned().collect()
     } else {
-        filter_list(list, search_term)
+        filter_list(list, &query)
+    };
+
+    for card in &mut list {
+        sort_games(&mut card.games, |game| game, options.sort_by, &query);
+        for library in [&mut card.lutris, &mut card.heroic].into_iter().flatten() {
+            sort_games(&mut library.games, |game| game, options.sort_by, &query);
+        }
+    }
+
+    let return_list = match options.list_view {
+        ListView::Cards => card_sections(&list, &query, options.collapsed_cards, focus),
+        ListView::AllGames => all_games_rows(&list, &query, options.sort_by, focus),
+        ListView::Libraries => library_sections(&list, &query, options.sort_by, focus),
+        ListView::Grid => game_grid(&list, &query, options.sort_by, options.artwork, focus),
     };
 
+    scrollable(column(return_list).width(Length::Fill)).id(list_scrollable_id())
+}
+
+/// Sorts a list of games, or of items holding a game, by the chosen sort.
+/// Sorting by name while searching keeps the best matches first
+fn sort_games<T>(items: &mut [T], game: impl Fn(&T) -> &Game, sort_by: SortBy, query: &Query) {
+    match sort_by {
+        SortBy::Name if query.has_terms() => {
+            items.sort_by_cached_key(|item| Reverse(query.score(game(item)).unwrap_or(0)))
+        }
+        SortBy::Name => items.sort_by_cached_key(|item| game(item).name.to_lowercase()),
+        SortBy::Size => items.sort_by_key(|item| Reverse(game(item).size)),
+        SortBy::LastUpdated => items.sort_by_key(|item| Reverse(game(item).updated)),
+        SortBy::LastPlayed => items.sort_by_key(|item| Reverse(game(item).last_played)),
+        SortBy::Playtime => items.sort_by_key(|item| Reverse(game(item).playtime)),
+    }
+}
+
+/// Today's default view, each card with its Steam, Lutris and Heroic games listed under it
+fn card_sections<'a>(
+    list: &[Card],
+    query: &Query,
+    collapsed_cards: &HashSet<String>,
+    focus: &mut Focusables,
+) -> Vec<Element<'a, Message>> {
+    let mut return_list: Vec<Element<Message>> = vec![];
+
     for card in list {
         // List is seperated by different SD cards
 
         // If the lists of games on the card are empty and the search_term isn't empty
         // We'll assume the card has been 'filtered out' and wont display it at all
-        if card.games.is_empty()
-            && match &card.heroic {
-                None => true,
-                Some(heroic_list) => heroic_list.games.is_empty(),
-            }
-            && match &card.lutris {
-                None => true,
-                Some(lutris_list) => lutris_list.games.is_empty(),
-            }
-            && !search_term.is_empty()
-        {
+        if scanning::card_games_count(card) == 0 && !query.is_empty() {
             continue;
         }
 
-        return_list.push(text(format!("Card: {}", card.name)).size(50).into());
+        let collapsed = collapsed_cards.contains(&card.uuid);
+        return_list.push(
+            row![
+                focus
+                    .button(
+                        text(if collapsed { "+" } else { "-" }).size(40),
+                        Message::ToggleCard(card.uuid.clone()),
+                    )
+                    .padding([0, 12]),
+                text(if card.archived {
+                    format!("Card: {} (Archived)", card.name)
+                } else {
+                    format!("Card: {}", card.name)
+                })
+                .size(50),
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .into(),
+        );
+
+        if collapsed {
+            continue;
+        }
 
         if !card.games.is_empty() {
             if card.heroic.is_some() || card.lutris.is_some() {
                 // Label the Steam Library if there's also Non Steam Libraries
-                return_list.push(
-                    container(
-                        text(format!("Steam Games"))
-                            .style(theming::STEAM_COLOR)
-                            .size(40),
-                    )
-                    .into(),
-                );
-                return_list.push(
-                    container(text(""))
-                        .width(Length::Units(theming::DIVIDER_BAR_LENGTH))
-                        .height(Length::Units(theming::DIVIDER_BAR_HEIGHT))
-                        .padding(4)
-                        .style(theming::STEAM_CONTAINER_STYLE)
-                        .into(),
-                );
+                return_list.extend(library_header(Library::Steam));
             }
 
             return_list.push(
-                //
                 card.games
                     .iter()
                     .fold(column![], |column: Column<Message>, game: &Game| {
-                        column.push(text(format!("{}", game.name)).size(30))
+                        column.push(game_entry(card, Library::Steam, game, query, focus))
                     })
                     .into(),
             );
         }
 
-        if let Some(library) = card.lutris {
-            if !library.games.is_empty() {
-                // If there were no games found or they were all filtered out by the search,
-                // don't add any elements to differentiate the other libraries
-                return_list.push(
-                    text(format!("Lutris Library"))
-                        .style(theming::LUTRIS_COLOR)
-                        .size(40)
-                        .into(),
-                );
-
-                // Add a divider line under the Lutris Library label
-                return_list.push(
-                    container(text(""))
-                        .width(Length::Units(theming::DIVIDER_BAR_LENGTH))
-                        .height(Length::Units(theming::DIVIDER_BAR_HEIGHT))
-                        .padding(4)
-                        .style(theming::LUTRIS_CONTAINER_STYLE)
-                        .into(),
-                );
-                return_list.push(
-                    library
-                        .games
-                        .iter()
-                        .fold(column![], |column: Column<Message>, game: &Game| {
-                            column.push(text(format!("{}", game.name)).size(30))
-                        })
-                        .into(),
-                );
+        for (library_type, library) in [
+            (Library::Lutris, &card.lutris),
+            (Library::Heroic, &card.heroic),
+        ] {
+            if let Some(library) = library {
+                if !library.games.is_empty() {
+                    // If there were no games found or they were all filtered out by the search,
+                    // don't add any elements to differentiate the other libraries
+                    return_list.extend(library_header(library_type));
+                    return_list.push(
+                        library
+                            .games
+                            .iter()
+                            .fold(column![], |column: Column<Message>, game: &Game| {
+                                column.push(game_entry(card, library_type, game, query, focus))
+                            })
+                            .into(),
+                    );
+                }
             }
         }
+    }
+
+    return_list
+}
+
+/// Every game from every card in one list, with columns for the card, library and size
+fn all_games_rows<'a>(
+    list: &[Card],
+    query: &Query,
+    sort_by: SortBy,
+    focus: &mut Focusables,
+) -> Vec<Element<'a, Message>> {
+    let mut entries = list_entries(list);
+    sort_games(&mut entries, |(_, _, game)| game, sort_by, query);
+
+    let mut return_list: Vec<Element<Message>> = vec![row![
+        container(text("Game").size(30)).width(Length::FillPortion(4)),
+        container(text("Card").size(30)).width(Length::FillPortion(2)),
+        container(text("Library").size(30)).width(Length::FillPortion(1)),
+        container(text("Size").size(30)).width(Length::FillPortion(1)),
+        container(text("Last Played").size(30)).width(Length::FillPortion(1)),
+        container(text("Playtime").size(30)).width(Length::FillPortion(1)),
+    ]
+    .into()];
+
+    for (card, library, game) in entries {
+        let (label, color, _) = library_style(library);
+        return_list.push(
+            row![
+                container(game_entry(card, library, game, query, focus))
+                    .width(Length::FillPortion(4)),
+                container(text(&card.name).size(30)).width(Length::FillPortion(2)),
+                container(text(label).style(color).size(30)).width(Length::FillPortion(1)),
+                container(text(game_size_label(game)).size(30)).width(Length::FillPortion(1)),
+                container(text(last_played_label(game)).size(30)).width(Length::FillPortion(1)),
+                container(text(playtime_label(game)).size(30)).width(Length::FillPortion(1)),
+            ]
+            .into(),
+        );
+    }
+
+    return_list
+}
+
+/// The games grouped into Steam, Lutris and Heroic sections, each game listed with the card it's on
+fn library_sections<'a>(
+    list: &[Card],
+    query: &Query,
+    sort_by: SortBy,
+    focus: &mut Focusables,
+) -> Vec<Element<'a, Message>> {
+    let mut entries = list_entries(list);
+    sort_games(&mut entries, |(_, _, game)| game, sort_by, query);
+
+    let mut return_list: Vec<Element<Message>> = vec![];
+
+    for library_type in [Library::Steam, Library::Lutris, Library::Heroic] {
+        let games: Vec<_> = entries
+            .iter()
+            .filter(|(_, library, _)| *library == library_type)
+            .collect();
+        if games.is_empty() {
+            continue;
+        }
+
+        return_list.extend(library_header(library_type));
+        for (card, library, game) in games {
+            return_list.push(
+                row![
+                    container(game_entry(card, *library, game, query, focus))
+                        .width(Length::FillPortion(4)),
+                    container(text(&card.name).size(30)).width(Length::FillPortion(2)),
+                    container(text(game_size_label(game)).size(30)).width(Length::FillPortion(1)),
+                    container(text(last_played_label(game)).size(30)).width(Length::FillPortion(1)),
+                    container(text(playtime_label(game)).size(30)).width(Length::FillPortion(1)),
+                ]
+                .into(),
+            );
+        }
+    }
+
+    return_list
+}
+
+/// Every game's cover art in rows, games without any art get a tile in their library's color
+/// with their initials instead
+fn game_grid<'a>(
+    list: &[Card],
+    query: &Query,
+    sort_by: SortBy,
+    artwork: &HashMap<u32, image::Handle>,
+    focus: &mut Focusables,
+) -> Vec<Element<'a, Message>> {
+    let mut entries = list_entries(list);
+    sort_games(&mut entries, |(_, _, game)| game, sort_by, query);
+
+    let width = Length::Units(artwork::THUMBNAIL_WIDTH as u16);
+    let height = Length::Units(artwork::THUMBNAIL_HEIGHT as u16);
+
+    focus.start_grid(GRID_COLUMNS);
+    let return_list = entries
+        .chunks(GRID_COLUMNS)
+        .map(|tiles| {
+            tiles
+                .iter()
+                .fold(
+                    row![].spacing(10),
+                    |row: Row<Message>, (card, library, game)| {
+                        let cover: Element<Message> =
+                            match game.appid.and_then(|appid| artwork.get(&appid)) {
+                                Some(handle) => {
+                                    image(handle.clone()).width(width).height(height).into()
+                                }
+                                None => container(text(artwork::initials(&game.name)).size(60))
+                                    .width(width)
+                                    .height(height)
+                                    .center_x()
+                                    .center_y()
+                                    .style(library_style(*library).2)
+                                    .into(),
+                            };
+                        let mut name = text(&game.name).size(20);
+                        if !game.problems.is_empty() {
+                            name = name.style(theming::ERROR_COLOR);
+                        } else if game.favourite {
+                            name = name.style(theming::HIGHLIGHT_COLOR);
+                        }
+
+                        row.push(
+                            focus
+                                .text_button(
+                                    column![cover, name, text(&card.name).size(16)]
+                                        .width(width)
+                                        .spacing(4),
+                                    Message::ShowGame(GameId {
+                                        card_uuid: card.uuid.clone(),
+                                        library: *library,
+                                        name: game.name.clone(),
+                                    }),
+                                )
+                                .padding(4),
+                        )
+                    },
+                )
+                .into()
+        })
+        .collect();
+    focus.end_grid();
+
+    return_list
+}
+
+/// Looks up the game's card and data in the list
+pub fn find_game<'a>(list: &'a [Card], game_id: &GameId) -> Option<(&'a Card, &'a Game)> {
+    let card = list.iter().find(|card| card.uuid == game_id.card_uuid)?;
+    let game = card
+        .library_games(game_id.library)
+        .iter()
+        .find(|game| game.name == game_id.name)?;
+
+    Some((card, game))
+}
+
+/// The other cards that also have a copy of the game, matched by app ID or by name for non Steam games
+pub fn other_cards_with_game<'a>(list: &'a [Card], card_uuid: &str, game: &Game) -> Vec<&'a Card> {
+    list.iter()
+        .filter(|card| card.uuid != card_uuid)
+        .filter(|card| {
+            list_entries(std::slice::from_ref(*card))
+                .into_iter()
+                .any(|(_, _, other)| match (game.appid, other.appid) {
+                    (Some(appid), Some(other_appid)) => appid == other_appid,
+                    _ => other.name.to_lowercase() == game.name.to_lowercase(),
+                })
+        })
+        .collect()
+}
 
-        if let Some(library) = card.heroic {
-            if !library.games.is_empty() {
-                return_list.push(
-                    text(format!("Heroic Library"))
-                        .style(theming::HEROIC_COLOR)
-                        .size(40)
-                        .into(),
-                );
-                return_list.push(
-                    container(text(""))
-                        .width(Length::Units(theming::DIVIDER_BAR_LENGTH))
-                        .height(Length::Units(theming::DIVIDER_BAR_HEIGHT))
-                        .padding(4)
-                        .style(theming::HEROIC_CONTAINER_STYLE)
-                        .into(),
-                );
-                return_list.push(
-                    library
-                        .games
-                        .iter()
-                        .fold(column![], |column: Column<Message>, game: &Game| {
-                            column.push(text(format!("{}", game.name)).size(30))
-                        })
-                        .into(),
-                );
+/// Flattens the cards into a (card, library, game) entry for every game
+fn list_entries(list: &[Card]) -> Vec<(&Card, Library, &Game)> {
+    let mut entries = vec![];
+
+    for card in list {
+        entries.extend(card.games.iter().map(|game| (card, Library::Steam, game)));
+        for (library_type, library) in [
+            (Library::Lutris, &card.lutris),
+            (Library::Heroic, &card.heroic),
+        ] {
+            if let Some(library) = library {
+                entries.extend(library.games.iter().map(|game| (card, library_type, game)));
             }
         }
     }
 
-    scrollable(column(return_list).width(Length::Fill))
+    entries
 }
 
-pub fn control_button(label: &str, message: Message) -> Element<Message> {
-    container(button(text(label).size(33)).padding(12).on_press(message))
-        .padding(4)
-        .into()
+/// The label, text color and divider style used for each library
+pub fn library_style(
+    library: Library,
+) -> (&'static str, Color, fn(&Theme) -> container::Appearance) {
+    match library {
+        Library::Steam => (
+            "Steam",
+            theming::STEAM_COLOR,
+            theming::STEAM_CONTAINER_STYLE,
+        ),
+        Library::Lutris => (
+            "Lutris",
+            theming::LUTRIS_COLOR,
+            theming::LUTRIS_CONTAINER_STYLE,
+        ),
+        Library::Heroic => (
+            "Heroic",
+            theming::HEROIC_COLOR,
+            theming::HEROIC_CONTAINER_STYLE,
+        ),
+    }
+}
+
+/// The colored library title with a divider bar under it
+fn library_header<'a>(library: Library) -> [Element<'a, Message>; 2] {
+    let (label, color, style) = library_style(library);
+    let title = match library {
+        Library::Steam => String::from("Steam Games"),
+        _ => format!("{label} Library"),
+    };
+
+    [
+        container(text(title).style(color).size(40)).into(),
+        container(text(""))
+            .width(Length::Units(theming::DIVIDER_BAR_LENGTH))
+            .height(Length::Units(theming::DIVIDER_BAR_HEIGHT))
+            .padding(4)
+            .style(style)
+            .into(),
+    ]
+}
+
+pub fn game_size_label(game: &Game) -> String {
+    match game.size {
+        Some(size) => scanning::format_size(size),
+        None => String::new(),
+    }
+}
+
+pub fn last_played_label(game: &Game) -> String {
+    match game.last_played {
+        Some(last_played) => scanning::format_date(last_played),
+        None => String::new(),
+    }
+}
+
+pub fn playtime_label(game: &Game) -> String {
+    match game.playtime {
+        Some(playtime) => scanning::format_playtime(playtime),
+        None => String::new(),
+    }
 }
 
-pub fn change_card_name(card_name: String, card_uuid: String, cards: &Vec<Card>) -> Vec<Card> {
-    let mut return_list: Vec<Card> = vec![];
-
-    for card in cards {
-        // For all the cards in the passed in saved list -
-        if card.uuid == card_uuid {
-            // Check if the current card matches the uuid of the card who's name were trying to change
-            // Push a clone card but with the name changed to the new passed in name
-            return_list.push(Card {
-                uuid: card.uuid.clone(),
-                name: card_name.clone(),
-                games: card.games.clone(),
-                heroic: card.heroic.clone(),
-                lutris: card.lutris.clone(),
+/// A game's name in the list, with the parts that matched the search drawn in the highlight color.
+/// Clicking it opens the game's detail page
+fn game_entry<'a>(
+    card: &Card,
+    library: Library,
+    game: &Game,
+    query: &Query,
+    focus: &mut Focusables,
+) -> Element<'a, Message> {
+    let mut name = query.highlight(&game.name).into_iter().fold(
+        row![],
+        |row: Row<Message>, (piece, is_match)| {
+            let piece = text(piece).size(30);
+            row.push(if is_match {
+                piece.style(theming::HIGHLIGHT_COLOR)
+            } else {
+                piece
             })
+        },
+    );
+    if game.favourite {
+        name = name.push(
+            text("  (Favourite)")
+                .size(20)
+                .style(theming::HIGHLIGHT_COLOR),
+        );
+    }
+    if !game.problems.is_empty() {
+        name = name.push(text("  (Suspect)").size(20).style(theming::ERROR_COLOR));
+    }
+
+    focus
+        .text_button(
+            name.align_items(Alignment::Center),
+            Message::ShowGame(GameId {
+                card_uuid: card.uuid.clone(),
+                library,
+                name: game.name.clone(),
+            }),
+        )
+        .padding(0)
+        .into()
+}
+
+/// Collects the buttons the controller can move between, in the order they're shown,
+/// and draws the focus ring around the focused one
+pub struct Focusables {
+    focused: Option<usize>,
+    /// The message of every focusable button, pressed when the button is focused and A is pressed
+    pub targets: Vec<Message>,
+    /// The buttons laid out in rows on the page, which the controller moves through by row and column
+    pub grid: Option<FocusGrid>,
+}
+
+/// A run of focusable buttons shown in rows of `columns` buttons
+#[derive(Debug, Clone, Copy)]
+pub struct FocusGrid {
+    /// The index of the grid's first button
+    pub first: usize,
+    /// How many buttons are in the grid
+    pub count: usize,
+    pub columns: usize,
+}
+
+impl FocusGrid {
+    pub fn contains(&self, index: usize) -> bool {
+        (self.first..self.first + self.count).contains(&index)
+    }
+}
+
+impl Focusables {
+    /// `focused` is the index of the button with the controller focus, if it's in this part of the window
+    pub fn new(focused: Option<usize>) -> Self {
+        Focusables {
+            focused,
+            targets: vec![],
+            grid: None,
+        }
+    }
+
+    /// The buttons added until `end_grid` are shown in rows of `columns`
+    pub fn start_grid(&mut self, columns: usize) {
+        self.grid = Some(FocusGrid {
+            first: self.targets.len(),
+            count: 0,
+            columns,
+        });
+    }
+
+    pub fn end_grid(&mut self) {
+        let added = self.targets.len();
+        if let Some(grid) = &mut self.grid {
+            grid.count = added - grid.first;
+        }
+    }
+
+    pub fn button<'a>(
+        &mut self,
+        content: impl Into<Element<'a, Message>>,
+        message: Message,
+    ) -> Button<'a, Message> {
+        self.add(content, message, false)
+    }
+
+    /// A text field that opens the on-screen keyboard when it's tapped or clicked, or when it's focused and A is pressed
+    pub fn text_field<'a>(
+        &mut self,
+        field: impl Into<Element<'a, Message>>,
+        target: KeyboardTarget,
+    ) -> Element<'a, Message> {
+        let focused = self.focused == Some(self.targets.len());
+        self.targets.push(Message::OpenKeyboard(target.clone()));
+
+        let field = field.into();
+        let width = field.as_widget().width();
+        let ring = container(KeyboardField::new(field, target))
+            .width(width)
+            .padding(3);
+        if focused {
+            ring.style(theming::FOCUSED_FIELD_CONTAINER_STYLE).into()
         } else {
-            // Otherwise push the original card to the return list
-            return_list.push(card.clone())
+            ring.into()
         }
     }
 
-    save_data_to_json(&return_list);
+    /// A button that looks like plain text until it's focused
+    pub fn text_button<'a>(
+        &mut self,
+        content: impl Into<Element<'a, Message>>,
+        message: Message,
+    ) -> Button<'a, Message> {
+        self.add(content, message, true)
+    }
 
-    return_list
+    fn add<'a>(
+        &mut self,
+        content: impl Into<Element<'a, Message>>,
+        message: Message,
+        text_button: bool,
+    ) -> Button<'a, Message> {
+        let focused = self.focused == Some(self.targets.len());
+        self.targets.push(message.clone());
+
+        button(content)
+            .style(theming::focus_style(focused, text_button))
+            .on_press(message)
+    }
+}
+
+pub fn control_button<'a>(
+    focus: &mut Focusables,
+    label: &str,
+    message: Message,
+) -> Element<'a, Message> {
+    container(focus.button(text(label).size(33), message).padding(12))
+        .padding(4)
+        .into()
+}
+
+/// Checks a new name for the card with the UUID, returns the trimmed name or why it can't be used
+pub fn validate_card_name(name: &str, card_uuid: &str, cards: &[Card]) -> Result<String, String> {
+    let name = name.trim();
+
+    if name.is_empty() {
+        return Err(String::from("A card's name can't be empty"));
+    }
+
+    let taken = cards
+        .iter()
+        .any(|card| card.uuid != card_uuid && card.name.trim().eq_ignore_ascii_case(name));
+    if taken {
+        return Err(format!("There's already a card named {name}"));
+    }
+
+    Ok(name.to_string())
 }
 
 pub fn settings_label<'a>(content: impl Into<Element<'a, Message>>) -> Element<'a, Message> {
@@ -267,26 +647,3 @@ pub fn long_settings_label<'a>(content: impl Into<Element<'a, Message>>) -> Elem
         .height(Length::Units(60))
         .into()
 }
-
-pub fn card_games_count(card: &Card) -> usize {
-    let mut count = card.games.len();
-
-    if let Some(heroic) = &card.heroic {
-        count += heroic.games.len();
-    }
-
-    if let Some(lutris) = &card.lutris {
-        count += lutris.games.len();
-    }
-
-    count
-}
-
-/// The default path to the SD card's root folder before v3.5 of SteamOS
-pub const OLD_SD_ROOT: &'static str = "/run/media/mmcblk0p1";
-/// The new mount folder for SD cards after v3.5 of SteamOS
-pub const NEW_SD_PATH: &'static str = "/run/media/deck";
-
-pub fn is_sd_card_line(line: &str) -> bool {
-    line.contains(OLD_SD_ROOT) | line.contains(NEW_SD_PATH) & line.contains("mmcblk")
-}
@@ -1,18 +1,38 @@
 use iced::{window, Application, Settings};
+use sdscanner::{cli, preferences, Preferences};
 
 mod app;
-mod scanning;
 
 use app::App;
 
 fn main() -> iced::Result {
-    let size = (1280, 800);
+    let (preferences, problems) = match preferences::load() {
+        Ok(preferences) => (preferences, vec![]),
+        Err(e) => (Preferences::default(), vec![e]),
+    };
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        for problem in &problems {
+            eprintln!("{problem}, using the default settings");
+        }
+    }
+    if cli::run(&args, &preferences) {
+        return Ok(());
+    }
+
+    let size = (
+        preferences.window_size.width,
+        preferences.window_size.height,
+    );
     App::run(Settings {
         window: window::Settings {
             size,
             // max_size: Some((1280, 800)),
             ..window::Settings::default()
         },
-        ..Settings::default()
+        // The app handles the close request itself so it can save first
+        exit_on_close_request: false,
+        ..Settings::with_flags((preferences, problems))
     })
 }